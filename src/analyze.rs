@@ -0,0 +1,296 @@
+use colored::*;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::cache::{load_cache, save_cache};
+use crate::config::Config;
+use crate::graph::{
+    GraphResult, Node, build_dependency_graph, dependency_order, find_cycles, node_key,
+    print_graph_warnings, read_source, source_key, topo_sort,
+};
+
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub files: usize,
+    pub loc: usize,
+    pub dependencies: usize,
+    pub max_depth: usize,
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// Builds the dependency graph for a main file, returning it with the root's node name
+fn load_graph(config: &Config, main_file: &str) -> Result<(HashMap<String, Node>, String), String> {
+    // First try the current directory, then fall back to src_dir
+    let main_path = if Path::new(main_file).exists() {
+        PathBuf::from(main_file)
+    } else {
+        config.src_dir.join(main_file)
+    };
+
+    if !main_path.exists() {
+        return Err(format!("File not found: {}", main_file));
+    }
+
+    let mut cache = load_cache(&config.cache_file);
+    let GraphResult { graph, warnings } =
+        build_dependency_graph(&main_path, config, &mut cache.graph);
+    save_cache(&config.cache_file, &cache);
+    print_graph_warnings(&warnings);
+
+    Ok((graph, source_key(&main_path, config)))
+}
+
+/// The longest dependency chain from each file, as its length (in files) and
+/// the dependency it continues with. Files are taken in `order`, dependencies
+/// first, so each chain is worked out once; a dependency not seen yet (the
+/// closing edge of a cycle) is ignored.
+fn longest_chains<'a>(
+    graph: &HashMap<String, Node>,
+    order: &'a [String],
+) -> HashMap<&'a str, (usize, Option<String>)> {
+    let mut best: HashMap<&str, (usize, Option<String>)> = HashMap::new();
+    for name in order {
+        let Some(node) = graph.get(name) else {
+            continue;
+        };
+        let mut length = 1;
+        let mut next = None;
+        for dep in &node.deps {
+            let dep = node_key(dep);
+            if let Some((dep_length, _)) = best.get(dep.as_str())
+                && dep_length + 1 > length
+            {
+                length = dep_length + 1;
+                next = Some(dep);
+            }
+        }
+        best.insert(name, (length, next));
+    }
+    best
+}
+
+/// Length (in edges) of the longest dependency chain below `root`, ignoring back edges
+fn max_depth(graph: &HashMap<String, Node>, root: &str) -> usize {
+    let order = dependency_order(graph);
+    longest_chains(graph, &order)
+        .get(root)
+        .map_or(0, |(length, _)| length - 1)
+}
+
+/// The longest dependency chain starting at `root`, root first. Each file's
+/// best chain is worked out after its dependencies', following the
+/// topological order; `using` cycles make this undefined and are reported as
+/// errors, while the closing edge of an import cycle is ignored.
+pub fn longest_path(graph: &HashMap<String, Node>, root: &str) -> Result<Vec<String>, String> {
+    let order = topo_sort(graph)?;
+    let best = longest_chains(graph, &order);
+
+    let mut path = Vec::new();
+    let mut current = graph.contains_key(root).then(|| root.to_string());
+    while let Some(name) = current {
+        current = best.get(name.as_str()).and_then(|(_, next)| next.clone());
+        path.push(name);
+    }
+    Ok(path)
+}
+
+pub fn compute_stats(graph: &HashMap<String, Node>, root: &str) -> ProjectStats {
+    let loc = graph
+        .values()
+        .filter_map(|node| read_source(&node.path))
+        .map(|content| content.lines().filter(|l| !l.trim().is_empty()).count())
+        .sum();
+
+    ProjectStats {
+        files: graph.len(),
+        loc,
+        dependencies: graph.values().map(|node| node.deps.len()).sum(),
+        max_depth: max_depth(graph, root),
+        cycles: find_cycles(graph),
+    }
+}
+
+pub fn show_stats(config: &Config, main_file: &str, json: bool) -> Result<(), String> {
+    let (graph, root) = load_graph(config, main_file)?;
+    let stats = compute_stats(&graph, &root);
+
+    if json {
+        let output = serde_json::to_string_pretty(&stats)
+            .map_err(|e| format!("Failed to serialize stats: {}", e))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!(
+        "{} Project stats for {}:\n",
+        "📊".cyan(),
+        root.bold().green()
+    );
+    println!("  {} Files:        {}", "•".blue(), stats.files);
+    println!("  {} Lines:        {}", "•".blue(), stats.loc);
+    println!("  {} Dependencies: {}", "•".blue(), stats.dependencies);
+    println!("  {} Max depth:    {}", "•".blue(), stats.max_depth);
+    if stats.cycles.is_empty() {
+        println!("  {} Cycles:       {}", "•".blue(), "none".green());
+    } else {
+        println!(
+            "  {} Cycles:       {}",
+            "•".blue(),
+            stats.cycles.len().to_string().red()
+        );
+        for cycle in &stats.cycles {
+            println!("      {}", cycle.join(" -> ").yellow());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn analyze_graph(
+    config: &Config,
+    main_file: &str,
+    cycles_only: bool,
+    longest: bool,
+) -> Result<(), String> {
+    let (graph, root) = load_graph(config, main_file)?;
+
+    if longest {
+        let path = longest_path(&graph, &root)?;
+        println!(
+            "{} Longest dependency chain: {} file(s)",
+            "🔗".cyan(),
+            path.len().to_string().bold()
+        );
+        println!("  {}", path.join(" -> ").yellow());
+        if path.len() > 1 {
+            println!(
+                "\n{} A change to {} means recompiling everything along this chain",
+                "ℹ️".cyan(),
+                path[path.len() - 1]
+            );
+        }
+        return Ok(());
+    }
+
+    if !cycles_only {
+        println!("{} Dependency graph:", "📊".cyan());
+        let mut names: Vec<&String> = graph.keys().collect();
+        names.sort();
+        for name in names {
+            println!("  {} -> {:?}", name, graph[name].deps);
+        }
+        println!();
+    }
+
+    let cycles = find_cycles(&graph);
+    if cycles.is_empty() {
+        println!("{} No cycles", "✓".green());
+        return Ok(());
+    }
+
+    println!(
+        "{} {} cycle(s) found:",
+        "🔄".red(),
+        cycles.len().to_string().red().bold()
+    );
+    for cycle in &cycles {
+        println!("  {} {}", "•".blue(), cycle.join(" -> ").yellow());
+    }
+
+    Err(format!("Dependency graph has {} cycle(s)", cycles.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, deps: Vec<String>) -> (String, Node) {
+        let node = Node {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            deps,
+            imports: Vec::new(),
+            implicit_deps: Vec::new(),
+        };
+        (name.to_string(), node)
+    }
+
+    /// `Main` on top of `levels` layers of two files each, where both files of
+    /// a layer depend on both files of the next: 2^levels distinct chains
+    fn layered_diamond(levels: usize) -> HashMap<String, Node> {
+        let layer = |level: usize| {
+            if level < levels {
+                vec![format!("L{}a.java", level), format!("L{}b.java", level)]
+            } else {
+                Vec::new()
+            }
+        };
+        let mut graph: HashMap<String, Node> = [node("Main.java", layer(0))].into();
+        for level in 0..levels {
+            for name in layer(level) {
+                graph.extend([node(&name, layer(level + 1))]);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn depth_of_a_deep_diamond_is_computed_without_walking_every_chain() {
+        let graph = layered_diamond(40);
+
+        assert_eq!(max_depth(&graph, "Main.java"), 40);
+        let path = longest_path(&graph, "Main.java").unwrap();
+        assert_eq!(path.len(), 41);
+        assert_eq!(path.first().map(String::as_str), Some("Main.java"));
+    }
+
+    #[test]
+    fn depth_ignores_the_edge_that_closes_a_cycle() {
+        let graph: HashMap<String, Node> = [
+            node("Main.java", vec!["A.java".to_string()]),
+            node("A.java", vec!["B.java".to_string()]),
+            node("B.java", vec!["A.java".to_string()]),
+        ]
+        .into();
+
+        assert_eq!(max_depth(&graph, "Main.java"), 2);
+    }
+
+    #[test]
+    fn longest_path_follows_the_longest_chain_from_the_root() {
+        let deps = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let graph: HashMap<String, Node> = [
+            node("Main.java", deps(&["D.java", "A.java", "E.java"])),
+            node("A.java", deps(&["B.java"])),
+            node("B.java", deps(&["C.java"])),
+            node("C.java", Vec::new()),
+            node("D.java", deps(&["C.java"])),
+            node("E.java", Vec::new()),
+        ]
+        .into();
+
+        assert_eq!(
+            longest_path(&graph, "Main.java").unwrap(),
+            ["Main.java", "A.java", "B.java", "C.java"]
+        );
+        assert_eq!(
+            longest_path(&graph, "D.java").unwrap(),
+            ["D.java", "C.java"]
+        );
+        assert!(longest_path(&graph, "Missing.java").unwrap().is_empty());
+    }
+
+    #[test]
+    fn longest_path_rejects_a_using_cycle() {
+        let graph: HashMap<String, Node> = [
+            node("A.java", vec!["B.java".to_string()]),
+            node("B.java", vec!["A.java".to_string()]),
+        ]
+        .into();
+
+        assert!(longest_path(&graph, "A.java").is_err());
+    }
+}
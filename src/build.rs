@@ -1,19 +1,402 @@
 use colored::*;
-use std::{fs, path::Path, process::Command};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Instant,
+};
 
-use crate::cache::{CacheEntry, compute_hash, load_cache, needs_rebuild, save_cache};
-use crate::config::Config;
-use crate::error_format::format_java_errors;
-use crate::graph::{build_dependency_graph, topo_sort};
+use crate::cache::{
+    Cache, CacheEntry, class_dir, class_name, compiler_settings_changed, compute_fingerprint,
+    compute_hash, find_class_files, load_cache, needs_rebuild, propagate_dirty, save_cache,
+};
+use crate::config::{CompilerConfig, Config};
+use crate::diagnostic::{Diagnostic, Severity, parse_javac_output};
+use crate::error_format::{
+    format_cached_warnings, format_java_errors, format_java_warnings, has_java_warnings,
+};
+use crate::graph::{
+    GraphResult, GraphWarning, Node, WarningKind, build_dependency_graph, dependency_file_for,
+    find_missing_deps, find_shadowed_stdlib, first_reference_line, module_descriptor,
+    print_graph_warnings, topo_sort,
+};
+use crate::hooks::run_hook;
+use crate::process::run_command;
+use crate::report::{
+    BuildResult, FileStatus, OutputFormat, ndjson_line, render_report, write_report_file,
+};
+use crate::resources::sync_resources;
+use crate::retry::retry_io;
+use crate::run::is_jshell_script;
+
+/// Prints a progress line: stdout normally, stderr when stdout carries a
+/// machine-readable report
+macro_rules! status {
+    ($ctx:expr, $($arg:tt)*) => {
+        if $ctx.format == OutputFormat::Pretty {
+            println!($($arg)*);
+        } else {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
 
 #[derive(Debug)]
 pub struct BuildContext {
     pub config: Config,
     pub verbose: bool,
     pub force: bool,
+    pub trace: bool,
+    pub emit_args_file: bool,
+    pub format: OutputFormat,
+    /// Where `--report` writes the build summary
+    pub report: Option<PathBuf>,
+    /// Print cache hit/miss counts after the build (`--cache-stats`)
+    pub cache_stats: bool,
+    /// Show the warnings up-to-date files had when they were last compiled
+    pub cached_warnings: bool,
+}
+
+/// How well the cache served a build
+struct CacheStats {
+    /// Files reused from the cache
+    hits: usize,
+    /// Files recompiled because they (or their class files) changed
+    changed: usize,
+    /// Files recompiled only because a dependency changed
+    invalidated: usize,
+}
+
+/// Source counts above this are passed to javac via an @argfile automatically
+const ARGFILE_THRESHOLD: usize = 200;
+
+/// Lint categories understood by `javac -Xlint:`
+const LINT_CATEGORIES: &[&str] = &[
+    "all",
+    "none",
+    "auxiliaryclass",
+    "cast",
+    "classfile",
+    "dangling-doc-comments",
+    "dep-ann",
+    "deprecation",
+    "divzero",
+    "empty",
+    "exports",
+    "fallthrough",
+    "finally",
+    "incubating",
+    "lossy-conversions",
+    "missing-explicit-ctor",
+    "module",
+    "opens",
+    "options",
+    "output-file-clash",
+    "overloads",
+    "overrides",
+    "path",
+    "preview",
+    "processing",
+    "rawtypes",
+    "removal",
+    "requires-automatic",
+    "requires-transitive-automatic",
+    "restricted",
+    "serial",
+    "static",
+    "strictfp",
+    "synchronization",
+    "text-blocks",
+    "this-escape",
+    "try",
+    "unchecked",
+    "varargs",
+];
+
+/// Builds the combined `-Xlint:a,b` flag, rejecting unknown categories
+pub fn lint_flag(categories: &[String]) -> Result<Option<String>, String> {
+    if categories.is_empty() {
+        return Ok(None);
+    }
+
+    let unknown: Vec<&str> = categories
+        .iter()
+        .map(String::as_str)
+        .filter(|category| {
+            !LINT_CATEGORIES.contains(&category.strip_prefix('-').unwrap_or(category))
+        })
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown lint categor{} in [compiler] lint: {}\n   Known categories: {}",
+            if unknown.len() == 1 { "y" } else { "ies" },
+            unknown.join(", "),
+            LINT_CATEGORIES.join(", ")
+        ));
+    }
+
+    Ok(Some(format!("-Xlint:{}", categories.join(","))))
+}
+
+/// javac flags that choose the Java version themselves
+const VERSION_FLAGS: &[&str] = &["--release", "-source", "--source", "-target", "--target"];
+
+/// Rejects `javac_opts` that set the Java version when `[compiler] release`
+/// already does; javac won't take `--release` twice or with `-source`/`-target`
+pub fn check_release_opts(compiler: &CompilerConfig) -> Result<(), String> {
+    if compiler.release.is_none() {
+        return Ok(());
+    }
+
+    // Long options may also be spelled `--source=17`
+    let conflicting: Vec<&str> = compiler
+        .javac_opts
+        .iter()
+        .map(|opt| opt.split('=').next().unwrap_or(opt))
+        .filter(|flag| VERSION_FLAGS.contains(flag))
+        .collect();
+    if conflicting.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "[compiler] release conflicts with {} in [compiler] javac_opts\n   Set the Java version in one place: drop release, or remove {} from javac_opts",
+        conflicting.join(", "),
+        if conflicting.len() == 1 { "it" } else { "them" }
+    ))
+}
+
+/// Reads the major version from `javac -version` output: `javac 21.0.2` is 21,
+/// and the old `javac 1.8.0_292` scheme is 8
+pub fn parse_javac_major(output: &str) -> Option<u32> {
+    let version = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("javac "))?;
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// Resolves `[compiler] release` to a Java version, running `javac -version` for "auto"
+pub fn release_version(release: Option<&str>, trace: bool) -> Result<Option<u32>, String> {
+    match release {
+        None => Ok(None),
+        Some("auto") => {
+            let output = run_command(Command::new("javac").arg("-version"), trace)
+                .map_err(|e| format!("Failed to run javac -version: {}", e))?;
+            // Old JDKs print the version to stderr
+            let text = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            parse_javac_major(&text)
+                .map(Some)
+                .ok_or_else(|| format!("Couldn't read the javac version from: {}", text.trim()))
+        }
+        Some(version) => version.parse().map(Some).map_err(|_| {
+            format!(
+                "Invalid [compiler] release `{}`: expected a Java version like \"17\" or \"auto\"",
+                version
+            )
+        }),
+    }
+}
+
+/// Quotes a path for a javac @argfile, where backslashes and quotes are escapes
+pub fn quote_argfile_entry(path: &Path) -> String {
+    let escaped = path
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+/// Adds the source files to a javac command. Long lists (or all of them, with
+/// `always_argfile`) go through an @argfile to stay under OS command-line
+/// limits; its path is returned so it can be removed once javac is done.
+fn add_sources(
+    cmd: &mut Command,
+    files: &[Node],
+    always_argfile: bool,
+) -> Result<Option<PathBuf>, String> {
+    if !always_argfile && files.len() <= ARGFILE_THRESHOLD {
+        for node in files {
+            cmd.arg(&node.path);
+        }
+        return Ok(None);
+    }
+
+    let path = env::temp_dir().join(format!("jfu-javac-{}.args", std::process::id()));
+    let contents: String = files
+        .iter()
+        .map(|node| format!("{}\n", quote_argfile_entry(&node.path)))
+        .collect();
+    fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write javac argument file: {}", e))?;
+
+    let mut arg = OsString::from("@");
+    arg.push(&path);
+    cmd.arg(arg);
+    Ok(Some(path))
+}
+
+/// Staging directory next to out_dir, so moving classes out of it is a cheap rename
+fn staging_dir(out_dir: &Path) -> PathBuf {
+    let name = out_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "out".to_string());
+    out_dir.with_file_name(format!(".{}-staging-{}", name, std::process::id()))
+}
+
+/// Makes sure out_dir exists and can be written to, so a read-only or
+/// permission-denied directory (common in sandboxed CI) fails right away
+fn ensure_out_dir_writable(out_dir: &Path) -> Result<(), String> {
+    let probe = out_dir.join(format!(".jfu-write-test-{}", std::process::id()));
+    let result = fs::create_dir_all(out_dir).and_then(|_| fs::write(&probe, b""));
+    let _ = fs::remove_file(&probe);
+
+    result.map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => format!(
+            "Output directory {} isn't writable ({}).\n   Check its permissions, or set `out_dir` in jfu.toml to somewhere writable (e.g. out_dir = \"/tmp/jfu-out\").",
+            out_dir.display(),
+            e
+        ),
+        _ => format!(
+            "Failed to create output directory {}: {}",
+            out_dir.display(),
+            e
+        ),
+    })
+}
+
+/// Moves every file under `from` into the same relative place under `to`
+fn merge_into(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            merge_into(&entry.path(), &target)?;
+        } else if retry_io(|| fs::rename(entry.path(), &target)).is_err() {
+            // Rename fails across filesystems; fall back to copying
+            retry_io(|| fs::copy(entry.path(), &target))?;
+        }
+    }
+    Ok(())
+}
+
+/// Per-file statuses for machine-readable reports. `failure` holds the
+/// diagnostics of a failed javac run: the files with errors failed, and the
+/// rest of the batch wasn't compiled because of them. If javac failed without
+/// blaming any file, the whole batch counts as failed.
+fn file_statuses<'a>(
+    skipped: &[&'a Node],
+    compiled: &'a [Node],
+    failure: Option<&[Diagnostic]>,
+) -> Vec<(&'a Node, FileStatus)> {
+    let has_errors = |node: &Node| {
+        failure.is_some_and(|diagnostics| {
+            diagnostics
+                .iter()
+                .any(|d| d.severity == Severity::Error && d.file == node.path)
+        })
+    };
+    let blamed = compiled.iter().any(has_errors);
+
+    let mut files: Vec<(&Node, FileStatus)> = skipped
+        .iter()
+        .map(|node| (*node, FileStatus::Skipped))
+        .collect();
+    files.extend(compiled.iter().map(|node| {
+        let status = match failure {
+            None => FileStatus::Compiled,
+            Some(_) if !blamed || has_errors(node) => FileStatus::Failed,
+            Some(_) => FileStatus::NotCompiled,
+        };
+        (node, status)
+    }));
+    files
+}
+
+/// Joins path entries with the platform's classpath separator
+pub fn join_classpath(paths: &[PathBuf]) -> Result<OsString, String> {
+    env::join_paths(paths).map_err(|e| format!("Invalid classpath entry: {}", e))
+}
+
+/// The classpath used for both compiling and running: compiled output plus the prelude
+pub fn project_classpath(config: &Config) -> Result<OsString, String> {
+    let mut entries = vec![config.out_dir.clone()];
+    if let Some(prelude) = &config.prelude_dir {
+        entries.push(prelude.clone());
+    }
+    join_classpath(&entries)
+}
+
+/// Writes the `--report` file and prints the `--format` report, if either was
+/// requested. Returns whether a report went to stdout.
+fn emit_report(ctx: &BuildContext, result: &BuildResult, started: Instant) -> Result<bool, String> {
+    if let Some(path) = &ctx.report {
+        write_report_file(path, result, started.elapsed())?;
+    }
+    match render_report(ctx.format, result) {
+        Some(report) => {
+            println!("{}", report);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Prints `--cache-stats`: this build's hits and misses, and the cache file's size and stale entries
+fn print_cache_stats(ctx: &BuildContext, stats: &CacheStats, cache: &Cache) {
+    let misses = stats.changed + stats.invalidated;
+    let total = stats.hits + misses;
+    let rate = (stats.hits * 100)
+        .checked_div(total)
+        .map(|rate| format!(", {}% hit rate", rate))
+        .unwrap_or_default();
+    status!(
+        ctx,
+        "       {} {} hit(s), {} miss(es) ({} changed, {} invalidated by a dependency){}",
+        "Cache".cyan().bold(),
+        stats.hits,
+        misses,
+        stats.changed,
+        stats.invalidated,
+        rate
+    );
+
+    // Entries whose class file is gone (after `jfu clean`, say) can't be reused
+    let stale = cache
+        .files
+        .values()
+        .filter(|entry| !Path::new(&entry.class_path).exists())
+        .count();
+    let size = fs::metadata(&ctx.config.cache_file)
+        .map(|meta| format!("{:.1} KiB", meta.len() as f64 / 1024.0))
+        .unwrap_or_else(|_| "not written".to_string());
+    status!(
+        ctx,
+        "             {}: {}, {} entries ({} stale)",
+        ctx.config.cache_file.display(),
+        size,
+        cache.files.len(),
+        stale
+    );
 }
 
-pub fn build_files(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
+/// Compiles `main_file` and everything it depends on, returning the dependency graph
+pub fn build_files(ctx: &BuildContext, main_file: &str) -> Result<HashMap<String, Node>, String> {
+    let started = Instant::now();
+
     // First try the current directory, then fall back to src_dir
     let main_path = if Path::new(main_file).exists() {
         main_file.into()
@@ -24,84 +407,393 @@ pub fn build_files(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
     if !main_path.exists() {
         return Err(format!("File not found: {}", main_file));
     }
+    if is_jshell_script(main_file) {
+        return Err(format!(
+            "`{}` is a jshell script and isn't compiled; run it with `jfu run {}`",
+            main_file, main_file
+        ));
+    }
 
     // No message here - will show in compilation phase
 
+    // Checked before any graph work, so a bad out_dir fails immediately
+    ensure_out_dir_writable(&ctx.config.out_dir)?;
+
+    // Generated sources have to exist before the graph is built
+    run_hook(ctx, "pre_build", ctx.config.hooks.pre_build.as_deref())?;
+
+    // Load cache
+    let mut cache = load_cache(&ctx.config.cache_file);
+
     // Build dependency graph
-    let graph = build_dependency_graph(
-        &main_path,
-        &ctx.config.src_dir,
-        ctx.config.auto_include_implicit_deps,
-    );
+    let GraphResult {
+        graph,
+        warnings: mut graph_warnings,
+    } = build_dependency_graph(&main_path, &ctx.config, &mut cache.graph);
+
+    // JSON reports carry these in `warnings`; everything else shows them right away
+    let show_warnings = |warnings: &[GraphWarning]| match ctx.format {
+        OutputFormat::Json => {}
+        OutputFormat::Ndjson => {
+            for warning in warnings {
+                println!("{}", ndjson_line("warning", warning));
+            }
+        }
+        OutputFormat::Pretty | OutputFormat::Sarif => print_graph_warnings(warnings),
+    };
+    show_warnings(&graph_warnings);
+
+    // Fail before javac gets a chance to report the missing classes confusingly
+    if ctx.config.strict_deps {
+        let missing = find_missing_deps(&graph);
+        if !missing.is_empty() {
+            save_cache(&ctx.config.cache_file, &cache);
+            let list: String = missing
+                .iter()
+                .map(|(file, dep)| format!("\n   `{}` (declared in {})", dep, file))
+                .collect();
+            return Err(format!(
+                "{} declared dependenc{} not found (strict_deps is on):{}",
+                missing.len(),
+                if missing.len() == 1 { "y" } else { "ies" },
+                list
+            ));
+        }
+    }
+
+    // Instructors can require every dependency to be declared with `using`
+    if ctx.config.fail_on_implicit {
+        let mut implicit: Vec<&GraphWarning> = graph_warnings
+            .iter()
+            .filter(|warning| warning.kind == WarningKind::ImplicitDependency)
+            .collect();
+        implicit.sort_by(|a, b| (&a.file, &a.detail).cmp(&(&b.file, &b.detail)));
+        if !implicit.is_empty() {
+            save_cache(&ctx.config.cache_file, &cache);
+            let list: String = implicit
+                .iter()
+                .map(|warning| {
+                    let line = graph
+                        .get(&warning.file)
+                        .and_then(|node| first_reference_line(&node.path, &warning.detail))
+                        .map(|line| format!(":{}", line))
+                        .unwrap_or_default();
+                    format!(
+                        "\n   `{}` used in {}{} (add 'using \"{}\"' to its header)",
+                        warning.detail,
+                        warning.file,
+                        line,
+                        dependency_file_for(&warning.detail, &ctx.config)
+                    )
+                })
+                .collect();
+            return Err(format!(
+                "{} undeclared dependenc{} (fail_on_implicit is on):{}",
+                implicit.len(),
+                if implicit.len() == 1 { "y" } else { "ies" },
+                list
+            ));
+        }
+    }
 
     if ctx.verbose {
-        println!("{} Dependency graph:", "📊".cyan());
-        for (name, node) in &graph {
-            println!("  {} -> {:?}", name, node.deps);
+        status!(ctx, "{} Dependency graph:", "📊".cyan());
+        let mut names: Vec<&String> = graph.keys().collect();
+        names.sort();
+        for name in names {
+            status!(ctx, "  {} -> {:?}", name, graph[name].deps);
         }
+
+        let shadowed = find_shadowed_stdlib(&graph);
+        show_warnings(&shadowed);
+        graph_warnings.extend(shadowed);
     }
 
     // Topological sort
     let build_order = topo_sort(&graph)?;
 
     if ctx.verbose {
-        println!("{} Build order: {:?}", "📋".cyan(), build_order);
+        status!(ctx, "{} Build order: {:?}", "📋".cyan(), build_order);
     }
 
-    // Create output directory
-    fs::create_dir_all(&ctx.config.out_dir)
-        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+    // Resources are refreshed on every build, so `jfu run` always sees the latest copies
+    for (source, dest) in sync_resources(&ctx.config)? {
+        if ctx.verbose {
+            status!(
+                ctx,
+                "  {} Copied {} -> {}",
+                "📎".cyan(),
+                source.display(),
+                dest.display()
+            );
+        }
+    }
 
-    // Load cache
-    let mut cache = load_cache(&ctx.config.cache_file);
+    // Reject bad lint categories before doing any work
+    let lint = lint_flag(&ctx.config.compiler.lint)?;
+    check_release_opts(&ctx.config.compiler)?;
+    let release = release_version(ctx.config.compiler.release.as_deref(), ctx.trace)?;
+
+    // A cache built for another release (say, by a different JDK with
+    // release = "auto") can't be reused
+    if let (Some(cached), Some(current)) = (cache.release, release)
+        && cached != current
+    {
+        eprintln!(
+            "{} Cached classes were compiled for Java {}, but this build targets Java {}. Is the cache shared with a machine on a different JDK? Rebuilding everything.",
+            "⚠️".yellow(),
+            cached,
+            current
+        );
+    }
+
+    // Changed compiler settings invalidate every cached class file
+    let settings_changed = compiler_settings_changed(&cache, &ctx.config.compiler, release);
+    if settings_changed && ctx.verbose && !cache.files.is_empty() {
+        status!(
+            ctx,
+            "{} Compiler settings changed, rebuilding everything",
+            "🔧".cyan()
+        );
+    }
+
+    // Hash every source once, on the thread pool; the hashes decide what's
+    // dirty and are stored in the cache for whatever gets compiled
+    if ctx.verbose {
+        status!(
+            ctx,
+            "{} Hashing {} file(s) on {} thread(s)",
+            "🧮".cyan(),
+            build_order.len(),
+            rayon::current_num_threads()
+        );
+    }
+    let mut hashes: HashMap<String, String> = build_order
+        .par_iter()
+        .filter_map(|file_name| {
+            graph
+                .get(file_name)
+                .map(|node| (file_name.clone(), compute_hash(&node.path)))
+        })
+        .collect();
 
     // Determine which files need rebuilding
+    let mut dirty: HashMap<String, bool> = hashes
+        .iter()
+        .map(|(file_name, hash)| {
+            let dirty = needs_rebuild(
+                &graph[file_name],
+                &cache,
+                &ctx.config.out_dir,
+                ctx.force || settings_changed,
+                hash,
+            );
+            (file_name.clone(), dirty)
+        })
+        .collect();
+    let changed = dirty.values().filter(|dirty| **dirty).count();
+    // A file compiled against an old version of a dependency is stale too
+    propagate_dirty(&graph, &mut dirty);
+
     let mut files_to_compile = Vec::new();
-    let mut skipped = 0;
+    let mut skipped_files = Vec::new();
 
     for file_name in &build_order {
-        if let Some(node) = graph.get(file_name) {
-            if needs_rebuild(node, &cache, &ctx.config.out_dir, ctx.force) {
-                files_to_compile.push(node.clone());
-            } else {
-                skipped += 1;
+        match dirty.get(file_name).copied() {
+            Some(true) => files_to_compile.push(graph[file_name].clone()),
+            Some(false) => {
+                skipped_files.push(&graph[file_name]);
                 if ctx.verbose {
-                    println!("  {} Skipped {} (no changes)", "✓".green(), file_name);
+                    status!(ctx, "  {} Skipped {} (no changes)", "✓".green(), file_name);
                 }
             }
+            None => {}
+        }
+    }
+
+    let skipped = skipped_files.len();
+
+    // Skipped files aren't recompiled, so their warnings would otherwise vanish.
+    // They go into every report; pretty output also prints them to stderr.
+    let replayed_warnings: Vec<Diagnostic> = if ctx.cached_warnings {
+        skipped_files
+            .iter()
+            .filter_map(|node| cache.files.get(&node.name))
+            .flat_map(|entry| entry.warnings.iter().cloned())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let replay_warnings = || {
+        if ctx.format == OutputFormat::Pretty && !replayed_warnings.is_empty() {
+            eprint!(
+                "{}",
+                format_cached_warnings(&replayed_warnings, &ctx.config.color_scheme)
+            );
+        }
+    };
+    let stats = CacheStats {
+        hits: skipped,
+        changed,
+        invalidated: dirty.len() - skipped - changed,
+    };
+
+    // A module descriptor isn't part of the `using` graph, but it has to be
+    // compiled with the module's sources whenever any of them change
+    if let Some(path) = module_descriptor(&ctx.config) {
+        let descriptor = Node {
+            name: "module-info.java".to_string(),
+            path,
+            deps: Vec::new(),
+            imports: Vec::new(),
+            implicit_deps: Vec::new(),
+        };
+        let hash = compute_hash(&descriptor.path);
+        if !files_to_compile.is_empty()
+            || needs_rebuild(
+                &descriptor,
+                &cache,
+                &ctx.config.out_dir,
+                ctx.force || settings_changed,
+                &hash,
+            )
+        {
+            hashes.insert(descriptor.name.clone(), hash);
+            files_to_compile.insert(0, descriptor);
         }
     }
 
     if files_to_compile.is_empty() {
-        println!(
+        // Persist any refreshed graph entries even when nothing compiles
+        save_cache(&ctx.config.cache_file, &cache);
+        replay_warnings();
+        status!(
+            ctx,
             "    {} {} class file(s) ({} up-to-date)",
             "Finished".green().bold(),
             skipped,
             skipped
         );
-        return Ok(());
+        if ctx.cache_stats {
+            print_cache_stats(ctx, &stats, &cache);
+        }
+        let result = BuildResult::new(
+            file_statuses(&skipped_files, &files_to_compile, None),
+            replayed_warnings.clone(),
+            graph_warnings,
+        );
+        emit_report(ctx, &result, started)?;
+        run_hook(ctx, "post_build", ctx.config.hooks.post_build.as_deref())?;
+        return Ok(graph);
     }
 
     // Compile files together in one javac invocation
-    println!(
+    status!(
+        ctx,
         "   {} {} file(s)",
         "Compiling".green().bold(),
         files_to_compile.len()
     );
 
+    // Atomic builds write to a staging directory that is merged in on success
+    let staging = ctx
+        .config
+        .atomic_output
+        .then(|| staging_dir(&ctx.config.out_dir));
+
     // Build javac command with all files
     let mut cmd = Command::new("javac");
-    cmd.arg("-d").arg(&ctx.config.out_dir);
+    cmd.arg("-d")
+        .arg(staging.as_ref().unwrap_or(&ctx.config.out_dir));
+    // Skipped (up-to-date) classes are resolved from out_dir
+    cmd.arg("-cp").arg(project_classpath(&ctx.config)?);
+    // The diagnostic parser keys on English markers like ": error:"
+    if !ctx.config.localized_diagnostics {
+        cmd.arg("-J-Duser.language=en").arg("-J-Duser.country=US");
+    }
 
-    for node in &files_to_compile {
-        cmd.arg(&node.path);
+    let compiler = &ctx.config.compiler;
+    if !compiler.module_path.is_empty() {
+        cmd.arg("--module-path")
+            .arg(join_classpath(&compiler.module_path)?);
+        // Classpath code only sees modules that are explicitly added
+        if module_descriptor(&ctx.config).is_none() {
+            cmd.arg("--add-modules").arg("ALL-MODULE-PATH");
+        }
+    }
+    if !compiler.processor_path.is_empty() {
+        cmd.arg("-processorpath")
+            .arg(join_classpath(&compiler.processor_path)?);
+    }
+    if !compiler.processors.is_empty() {
+        cmd.arg("-processor").arg(compiler.processors.join(","));
+    }
+    if let Some(flag) = lint {
+        cmd.arg(flag);
     }
+    if let Some(release) = release {
+        cmd.arg("--release").arg(release.to_string());
+    }
+    // Anything else the user wants; sources always come last
+    cmd.args(&compiler.javac_opts);
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run javac: {}", e))?;
+    let argfile = add_sources(&mut cmd, &files_to_compile, ctx.emit_args_file)?;
+    if let Some(path) = &argfile
+        && ctx.verbose
+    {
+        status!(
+            ctx,
+            "{} Passing {} file(s) via {}",
+            "📝".cyan(),
+            files_to_compile.len(),
+            path.display()
+        );
+    }
+
+    // Clear the old outputs so nested classes removed from a source don't linger.
+    // Atomic builds leave out_dir alone until javac has succeeded.
+    let clear_old_outputs = || {
+        for node in &files_to_compile {
+            for stale in find_class_files(&ctx.config.out_dir, node) {
+                let _ = retry_io(|| fs::remove_file(&stale));
+            }
+        }
+    };
+    if staging.is_none() {
+        clear_old_outputs();
+    }
+
+    let output = run_command(&mut cmd, ctx.trace);
+    if let Some(path) = argfile {
+        let _ = fs::remove_file(path);
+    }
+    let output = output.map_err(|e| format!("Failed to run javac: {}", e));
+
+    if let Some(staging) = &staging {
+        let merged = match &output {
+            Ok(output) if output.status.success() => {
+                clear_old_outputs();
+                merge_into(staging, &ctx.config.out_dir).map_err(|e| {
+                    format!(
+                        "Failed to move compiled classes into {}: {}",
+                        ctx.config.out_dir.display(),
+                        e
+                    )
+                })
+            }
+            _ => Ok(()),
+        };
+        let _ = fs::remove_dir_all(staging);
+        merged?;
+    }
+    let output = output?;
 
     if !output.status.success() {
+        // Whatever javac wrote before failing is mixed in with the old output
+        if staging.is_none() && !ctx.config.keep_partial_output {
+            clear_old_outputs();
+        }
+
         let stderr = String::from_utf8_lossy(&output.stderr);
         let stdout = String::from_utf8_lossy(&output.stdout);
 
@@ -112,28 +804,83 @@ pub fn build_files(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
             stderr.to_string()
         };
 
-        return Err(format_java_errors(&error_output));
+        // Keep the refreshed graph entries; failed files stay out of the cache
+        save_cache(&ctx.config.cache_file, &cache);
+
+        let mut diagnostics = parse_javac_output(&error_output);
+        let statuses = file_statuses(&skipped_files, &files_to_compile, Some(&diagnostics));
+        diagnostics.extend(replayed_warnings.iter().cloned());
+        let result = BuildResult::new(statuses, diagnostics, graph_warnings);
+        if emit_report(ctx, &result, started)? {
+            return Err("Compilation failed".to_string());
+        }
+        return Err(format_java_errors(&error_output, &ctx.config));
     }
 
+    // javac reports warnings on stderr even when compilation succeeds
+    let warnings = String::from_utf8_lossy(&output.stderr);
+    let diagnostics = parse_javac_output(&warnings);
+    let result = BuildResult::new(
+        file_statuses(&skipped_files, &files_to_compile, None),
+        diagnostics
+            .iter()
+            .chain(&replayed_warnings)
+            .cloned()
+            .collect(),
+        graph_warnings,
+    );
+    if !emit_report(ctx, &result, started)? && has_java_warnings(&warnings) {
+        eprint!(
+            "{}",
+            format_java_warnings(&warnings, &ctx.config.color_scheme)
+        );
+    }
+    replay_warnings();
+
     // Update cache for all compiled files
-    for node in &files_to_compile {
-        let class_name = node.name.strip_suffix(".java").unwrap_or(&node.name);
-        let class_path = ctx.config.out_dir.join(format!("{}.class", class_name));
+    let entries: Vec<(String, CacheEntry)> = files_to_compile
+        .par_iter()
+        .map(|node| {
+            let class_path = class_dir(&ctx.config.out_dir, &node.path)
+                .join(format!("{}.class", class_name(node)));
+            let class_files: Vec<String> = find_class_files(&ctx.config.out_dir, node)
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
 
-        cache.insert(
-            node.name.clone(),
-            CacheEntry {
-                hash: compute_hash(&node.path),
-                class_path: class_path.to_string_lossy().to_string(),
-            },
-        );
+            (
+                node.name.clone(),
+                CacheEntry {
+                    hash: hashes[&node.name].clone(),
+                    class_path: class_path.to_string_lossy().to_string(),
+                    class_files,
+                    deps: node.deps.clone(),
+                    warnings: diagnostics
+                        .iter()
+                        .filter(|diagnostic| {
+                            diagnostic.severity == Severity::Warning && diagnostic.file == node.path
+                        })
+                        .cloned()
+                        .collect(),
+                },
+            )
+        })
+        .collect();
+    for (name, entry) in &entries {
+        for file in &entry.class_files {
+            cache.class_owners.insert(file.clone(), name.clone());
+        }
     }
+    cache.files.extend(entries);
 
     // Save cache
+    cache.compiler_fingerprint = compute_fingerprint(&ctx.config.compiler);
+    cache.release = release;
     save_cache(&ctx.config.cache_file, &cache);
 
     if skipped > 0 {
-        println!(
+        status!(
+            ctx,
             "    {} {} class file(s) ({} compiled, {} up-to-date)",
             "Finished".green().bold(),
             files_to_compile.len() + skipped,
@@ -141,13 +888,235 @@ pub fn build_files(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
             skipped
         );
     } else {
-        println!(
+        status!(
+            ctx,
             "    {} {} class file(s) ({} compiled)",
             "Finished".green().bold(),
             files_to_compile.len(),
             files_to_compile.len()
         );
     }
+    if ctx.cache_stats {
+        print_cache_stats(ctx, &stats, &cache);
+    }
 
-    Ok(())
+    run_hook(ctx, "post_build", ctx.config.hooks.post_build.as_deref())?;
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            deps: Vec::new(),
+            imports: Vec::new(),
+            implicit_deps: Vec::new(),
+        }
+    }
+
+    fn statuses(files: Vec<(&Node, FileStatus)>) -> Vec<(String, FileStatus)> {
+        files
+            .into_iter()
+            .map(|(node, status)| (node.name.clone(), status))
+            .collect()
+    }
+
+    #[test]
+    fn only_files_with_errors_fail_a_failed_batch() {
+        let cached = node("Cached.java");
+        let compiled = [node("Broken.java"), node("Fine.java")];
+        let diagnostics = parse_javac_output(
+            "\
+Broken.java:3: error: cannot find symbol
+        undefined();
+        ^
+  symbol:   method undefined()
+  location: class Broken
+Fine.java:2: warning: [cast] redundant cast to int
+        int i = (int) 3;
+                ^
+1 error
+1 warning
+",
+        );
+
+        let files = file_statuses(&[&cached], &compiled, Some(&diagnostics));
+        assert_eq!(
+            statuses(files),
+            [
+                ("Cached.java".to_string(), FileStatus::Skipped),
+                ("Broken.java".to_string(), FileStatus::Failed),
+                ("Fine.java".to_string(), FileStatus::NotCompiled),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_failure_that_blames_no_file_fails_the_whole_batch() {
+        let compiled = [node("A.java"), node("B.java")];
+        let diagnostics = parse_javac_output("error: invalid flag: -bogus\n");
+
+        let files = file_statuses(&[], &compiled, Some(&diagnostics));
+        assert!(
+            files
+                .iter()
+                .all(|(_, status)| *status == FileStatus::Failed)
+        );
+    }
+
+    #[test]
+    fn lint_categories_become_one_xlint_flag() {
+        let categories =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|name| name.to_string()).collect() };
+
+        assert_eq!(lint_flag(&[]), Ok(None));
+        assert_eq!(
+            lint_flag(&categories(&["unchecked", "deprecation", "-serial"])),
+            Ok(Some("-Xlint:unchecked,deprecation,-serial".to_string()))
+        );
+
+        let error = lint_flag(&categories(&["unchecked", "bogus", "-nope"])).unwrap_err();
+        assert!(error.starts_with("Unknown lint categories in [compiler] lint: bogus, -nope"));
+    }
+
+    #[test]
+    fn javac_major_version_is_read_from_either_numbering_scheme() {
+        assert_eq!(parse_javac_major("javac 21.0.2\n"), Some(21));
+        assert_eq!(parse_javac_major("javac 17"), Some(17));
+        assert_eq!(parse_javac_major("javac 1.8.0_292\n"), Some(8));
+        assert_eq!(
+            parse_javac_major("Picked up JAVA_TOOL_OPTIONS: -Xmx1g\njavac 11.0.20\n"),
+            Some(11)
+        );
+        assert_eq!(parse_javac_major("openjdk 17.0.1"), None);
+    }
+
+    #[test]
+    fn configured_release_must_be_a_java_version() {
+        assert_eq!(release_version(None, false), Ok(None));
+        assert_eq!(release_version(Some("17"), false), Ok(Some(17)));
+        let error = release_version(Some("seventeen"), false).unwrap_err();
+        assert!(error.starts_with("Invalid [compiler] release `seventeen`"));
+    }
+
+    #[test]
+    fn release_conflicts_with_version_flags_in_javac_opts() {
+        let compiler = |release: Option<&str>, opts: &[&str]| CompilerConfig {
+            release: release.map(str::to_string),
+            javac_opts: opts.iter().map(|opt| opt.to_string()).collect(),
+            ..CompilerConfig::default()
+        };
+
+        assert!(check_release_opts(&compiler(Some("17"), &["-g", "-parameters"])).is_ok());
+        assert!(check_release_opts(&compiler(None, &["-source", "11", "-target", "11"])).is_ok());
+
+        let error = check_release_opts(&compiler(Some("17"), &["-source", "11", "--target=11"]))
+            .unwrap_err();
+        assert!(error.contains("conflicts with -source, --target in [compiler] javac_opts"));
+        assert!(check_release_opts(&compiler(Some("auto"), &["--release", "11"])).is_err());
+    }
+
+    #[test]
+    fn a_successful_batch_is_compiled() {
+        let compiled = [node("A.java")];
+        let files = file_statuses(&[], &compiled, None);
+        assert_eq!(
+            statuses(files),
+            [("A.java".to_string(), FileStatus::Compiled)]
+        );
+    }
+
+    #[test]
+    fn merging_moves_nested_outputs_over_the_old_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let staging = staging_dir(&dir.path().join("out"));
+        let out = dir.path().join("out");
+        assert_eq!(staging.parent(), Some(dir.path()));
+
+        fs::create_dir_all(staging.join("com/example")).unwrap();
+        fs::write(staging.join("Main.class"), "new main").unwrap();
+        fs::write(staging.join("com/example/Util.class"), "new util").unwrap();
+        fs::create_dir_all(&out).unwrap();
+        fs::write(out.join("Main.class"), "old main").unwrap();
+        fs::write(out.join("Other.class"), "other").unwrap();
+
+        merge_into(&staging, &out).unwrap();
+        assert_eq!(
+            fs::read_to_string(out.join("Main.class")).unwrap(),
+            "new main"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("com/example/Util.class")).unwrap(),
+            "new util"
+        );
+        assert_eq!(
+            fs::read_to_string(out.join("Other.class")).unwrap(),
+            "other"
+        );
+        assert!(!staging.join("Main.class").exists());
+    }
+
+    #[test]
+    fn sources_beyond_the_threshold_go_through_an_argfile() {
+        let files: Vec<Node> = (0..=ARGFILE_THRESHOLD)
+            .map(|i| node(&format!("src/File {}.java", i)))
+            .collect();
+
+        let mut cmd = Command::new("javac");
+        assert_eq!(
+            add_sources(&mut cmd, &files[..ARGFILE_THRESHOLD], false),
+            Ok(None)
+        );
+        assert_eq!(cmd.get_args().count(), ARGFILE_THRESHOLD);
+
+        let mut cmd = Command::new("javac");
+        let argfile = add_sources(&mut cmd, &files, false).unwrap().unwrap();
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args, [format!("@{}", argfile.display()).as_str()]);
+        let listed = fs::read_to_string(&argfile).unwrap();
+        let _ = fs::remove_file(&argfile);
+        assert_eq!(listed.lines().count(), files.len());
+        assert_eq!(listed.lines().next(), Some("\"src/File 0.java\""));
+    }
+
+    #[test]
+    fn unusable_out_dir_fails_with_a_clear_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        assert_eq!(ensure_out_dir_writable(&out), Ok(()));
+        assert!(out.is_dir());
+        // The write probe is cleaned up
+        assert_eq!(fs::read_dir(&out).unwrap().count(), 0);
+
+        let file = dir.path().join("file");
+        fs::write(&file, "").unwrap();
+        let err = ensure_out_dir_writable(&file.join("out")).unwrap_err();
+        assert!(
+            err.starts_with("Failed to create output directory"),
+            "{err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn read_only_out_dir_suggests_remedies() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        fs::create_dir(&out).unwrap();
+        fs::set_permissions(&out, fs::Permissions::from_mode(0o555)).unwrap();
+        // Permissions don't apply to root, as in many CI containers
+        if fs::write(out.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let err = ensure_out_dir_writable(&out).unwrap_err();
+        assert!(err.contains("isn't writable"), "{err}");
+        assert!(err.contains("set `out_dir` in jfu.toml to somewhere writable"));
+    }
 }
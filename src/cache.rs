@@ -1,29 +1,104 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
 
-use crate::graph::Node;
+use crate::config::CompilerConfig;
+use crate::diagnostic::Diagnostic;
+use crate::graph::{Node, node_key, read_package};
+use crate::retry::retry_io;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CacheEntry {
     pub hash: String,
     pub class_path: String,
+    /// Every class file the source produced, including nested and anonymous
+    /// classes (`Foo$Bar.class`, `Foo$1.class`)
+    #[serde(default)]
+    pub class_files: Vec<String>,
+    /// Dependencies the file had when it was compiled
+    #[serde(default)]
+    pub deps: Vec<String>,
+    /// javac warnings from that compile, shown again while the file is up to date
+    #[serde(default)]
+    pub warnings: Vec<Diagnostic>,
 }
 
-pub type Cache = HashMap<String, CacheEntry>;
+/// Parsed dependency information for a single source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedSource {
+    pub mtime: u64,
+    pub len: u64,
+    /// Stamp of the `.java` files next to it when it was parsed, since they
+    /// decide its implicit dependencies
+    #[serde(default)]
+    pub siblings: String,
+    pub deps: Vec<String>,
+    #[serde(default)]
+    pub imports: Vec<String>,
+    pub implicit_deps: Vec<String>,
+}
+
+/// Cached dependency parsing results, keyed by source path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GraphCache {
+    /// The `.java` files under src_dir (subdirectories included) when the
+    /// entries were recorded
+    #[serde(default)]
+    pub source_files: Vec<String>,
+    /// Fingerprint of the config options that affect dependency detection
+    #[serde(default)]
+    pub settings: String,
+    #[serde(default)]
+    pub entries: HashMap<String, ParsedSource>,
+}
+
+/// Whether a source declares a `main` method, for entrypoint auto-detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MainScan {
+    pub mtime: u64,
+    pub len: u64,
+    pub has_main: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    /// Fingerprint of the compiler settings the cached classes were built with
+    #[serde(default)]
+    pub compiler_fingerprint: String,
+    /// The `--release` the cached classes were compiled for, when one is pinned
+    #[serde(default)]
+    pub release: Option<u32>,
+    #[serde(default)]
+    pub files: HashMap<String, CacheEntry>,
+    /// The source that last wrote each class file. Same-named sources in
+    /// different folders compile to the same `out/Main.class`, so a class
+    /// file only counts as up to date for the source that wrote it
+    #[serde(default)]
+    pub class_owners: HashMap<String, String>,
+    #[serde(default)]
+    pub graph: GraphCache,
+    /// Main-method scan results, keyed by source path
+    #[serde(default)]
+    pub main_scans: HashMap<String, MainScan>,
+}
 
 pub fn load_cache(cache_path: &Path) -> Cache {
     if cache_path.exists() {
         let content = fs::read_to_string(cache_path).unwrap_or_default();
         serde_json::from_str(&content).unwrap_or_default()
     } else {
-        HashMap::new()
+        Cache::default()
     }
 }
 
 pub fn save_cache(cache_path: &Path, cache: &Cache) {
     let json = serde_json::to_string_pretty(cache).unwrap();
-    fs::write(cache_path, json).unwrap_or_else(|e| {
+    retry_io(|| fs::write(cache_path, &json)).unwrap_or_else(|e| {
         use colored::*;
         eprintln!("{} Failed to save cache: {}", "⚠️".yellow(), e);
     });
@@ -36,13 +111,78 @@ pub fn compute_hash(path: &Path) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-pub fn needs_rebuild(node: &Node, cache: &Cache, out_dir: &Path, force: bool) -> bool {
+/// Hashes any serializable settings so changes can be detected between builds
+pub fn compute_fingerprint<T: Serialize>(settings: &T) -> String {
+    let json = serde_json::to_string(settings).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(json);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Whether the cached classes were compiled with other compiler settings, or
+/// for another `--release`, which invalidates every one of them
+pub fn compiler_settings_changed(
+    cache: &Cache,
+    compiler: &CompilerConfig,
+    release: Option<u32>,
+) -> bool {
+    cache.compiler_fingerprint != compute_fingerprint(compiler) || cache.release != release
+}
+
+/// Returns the (mtime in nanoseconds, length) stamp used to detect source changes
+pub fn source_stamp(path: &Path) -> Option<(u64, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((mtime.as_nanos() as u64, meta.len()))
+}
+
+/// Directory javac writes a source's classes to, following its package
+pub fn class_dir(out_dir: &Path, source: &Path) -> PathBuf {
+    let mut dir = out_dir.to_path_buf();
+    if let Some(package) = read_package(source) {
+        dir.extend(package.split('.'));
+    }
+    dir
+}
+
+/// The simple name of the class compiled from a node (`com/a/Util.java` -> `Util`)
+pub fn class_name(node: &Node) -> String {
+    node.path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Lists `Foo.class` and `Foo$*.class` for a compiled `Foo.java`
+pub fn find_class_files(out_dir: &Path, node: &Node) -> Vec<PathBuf> {
+    let class_name = class_name(node);
+    let nested_prefix = format!("{}$", class_name);
+    let Ok(entries) = fs::read_dir(class_dir(out_dir, &node.path)) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".class"))
+                .is_some_and(|stem| stem == class_name || stem.starts_with(&nested_prefix))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether a file has to be recompiled. `hash` is the file's current
+/// `compute_hash`, computed once by the caller so it can be stored afterwards.
+pub fn needs_rebuild(node: &Node, cache: &Cache, out_dir: &Path, force: bool, hash: &str) -> bool {
     if force {
         return true;
     }
 
-    let class_name = node.name.strip_suffix(".java").unwrap_or(&node.name);
-    let class_path = out_dir.join(format!("{}.class", class_name));
+    let class_path = class_dir(out_dir, &node.path).join(format!("{}.class", class_name(node)));
 
     // If .class doesn't exist, rebuild
     if !class_path.exists() {
@@ -50,11 +190,122 @@ pub fn needs_rebuild(node: &Node, cache: &Cache, out_dir: &Path, force: bool) ->
     }
 
     // If not in cache, rebuild
-    let Some(entry) = cache.get(&node.name) else {
+    let Some(entry) = cache.files.get(&node.name) else {
         return true;
     };
 
+    // If another source has overwritten the class file since, rebuild
+    if cache.class_owners.get(&entry.class_path) != Some(&node.name) {
+        return true;
+    }
+
+    // If any nested class file went missing, rebuild
+    if entry
+        .class_files
+        .iter()
+        .any(|file| !Path::new(file).exists())
+    {
+        return true;
+    }
+
+    // If the dependency set changed (e.g. a `using` line was added, or an
+    // auto-included implicit dependency appeared), rebuild
+    if !same_deps(&node.deps, &entry.deps) {
+        return true;
+    }
+
     // If hash changed, rebuild
-    let current_hash = compute_hash(&node.path);
-    current_hash != entry.hash
+    hash != entry.hash
+}
+
+/// Marks every file that depends, directly or transitively, on a dirty file as
+/// dirty too, so dependents are recompiled against the new classes.
+/// `dirty` holds `needs_rebuild` for each file in the graph.
+pub fn propagate_dirty(graph: &HashMap<String, Node>, dirty: &mut HashMap<String, bool>) {
+    // Repeat until nothing changes; cycles are fine this way
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (name, node) in graph {
+            if dirty.get(name) == Some(&false)
+                && node
+                    .deps
+                    .iter()
+                    .any(|dep| dirty.get(&node_key(dep)) == Some(&true))
+            {
+                dirty.insert(name.clone(), true);
+                changed = true;
+            }
+        }
+    }
+}
+
+fn same_deps(current: &[String], cached: &[String]) -> bool {
+    let mut current = current.to_vec();
+    let mut cached = cached.to_vec();
+    current.sort();
+    cached.sort();
+    current == cached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, deps: &[&str]) -> (String, Node) {
+        let node = Node {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            deps: deps.iter().map(|dep| dep.to_string()).collect(),
+            imports: Vec::new(),
+            implicit_deps: Vec::new(),
+        };
+        (name.to_string(), node)
+    }
+
+    #[test]
+    fn classes_compiled_for_another_release_are_invalid() {
+        let compiler = CompilerConfig {
+            release: Some("auto".to_string()),
+            ..CompilerConfig::default()
+        };
+        let cache = Cache {
+            compiler_fingerprint: compute_fingerprint(&compiler),
+            release: Some(11),
+            ..Cache::default()
+        };
+
+        assert!(!compiler_settings_changed(&cache, &compiler, Some(11)));
+        // "auto" resolved to another JDK
+        assert!(compiler_settings_changed(&cache, &compiler, Some(17)));
+        let linted = CompilerConfig {
+            lint: vec!["cast".to_string()],
+            ..compiler.clone()
+        };
+        assert!(compiler_settings_changed(&cache, &linted, Some(11)));
+    }
+
+    #[test]
+    fn a_changed_file_dirties_everything_that_depends_on_it() {
+        // A uses B, B uses C
+        let graph: HashMap<String, Node> = [
+            node("A.java", &["B.java"]),
+            node("B.java", &["C.java"]),
+            node("C.java", &[]),
+            node("Other.java", &[]),
+        ]
+        .into_iter()
+        .collect();
+        let mut dirty: HashMap<String, bool> = graph
+            .keys()
+            .map(|name| (name.clone(), name == "C.java"))
+            .collect();
+
+        propagate_dirty(&graph, &mut dirty);
+
+        assert!(dirty["A.java"]);
+        assert!(dirty["B.java"]);
+        assert!(dirty["C.java"]);
+        assert!(!dirty["Other.java"]);
+    }
 }
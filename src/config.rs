@@ -1,6 +1,14 @@
 use colored::*;
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::cache::{load_cache, save_cache};
+use crate::graph::{find_main_files, find_main_files_named};
+use crate::syntax::DEFAULT_THEME;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -14,8 +22,144 @@ pub struct Config {
     pub jvm_opts: Vec<String>,
     #[serde(default)]
     pub entrypoint: Option<String>,
+    /// Named entrypoints, selected on the command line with `@name`
+    #[serde(default)]
+    pub entrypoints: BTreeMap<String, String>,
     #[serde(default)]
     pub auto_include_implicit_deps: bool,
+    /// Also treat package-private top-level types as implicit-dependency candidates
+    #[serde(default)]
+    pub include_package_private_types: bool,
+    /// How many lines of each file are scanned for implicit dependencies
+    #[serde(default = "default_reference_scan_lines")]
+    pub reference_scan_lines: usize,
+    /// Shared classes that are always on the classpath (e.g. course-provided helpers)
+    #[serde(default)]
+    pub prelude_dir: Option<PathBuf>,
+    /// Non-Java files copied into out_dir (and so onto the classpath). Files are
+    /// copied as-is; directories have their contents copied
+    #[serde(default)]
+    pub resources: Vec<PathBuf>,
+    /// Compile into a staging directory and only move the classes into out_dir
+    /// on success, so a failed build never leaves out_dir half-updated
+    #[serde(default)]
+    pub atomic_output: bool,
+    /// Keep the class files a failed build did manage to write (for inspection)
+    /// instead of removing them. Atomic builds never touch out_dir on failure.
+    #[serde(default = "default_keep_partial_output")]
+    pub keep_partial_output: bool,
+    /// Let javac report diagnostics in the system language; jfu's error
+    /// formatting only understands English output
+    #[serde(default)]
+    pub localized_diagnostics: bool,
+    /// Treat a missing `using` dependency as an error instead of a warning
+    #[serde(default)]
+    pub strict_deps: bool,
+    /// Treat implicit dependencies (referenced but not declared with `using`) as
+    /// errors instead of warnings
+    #[serde(default)]
+    pub fail_on_implicit: bool,
+    /// Enter symlinked directories when searching src_dir and watching for
+    /// changes. Off by default, since links back up the tree would loop
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Worker threads for hashing and other parallel work (defaults to the CPU count)
+    #[serde(default)]
+    pub threads: Option<usize>,
+    /// Lay formatted errors out for this many columns instead of the
+    /// detected terminal width (also `--width` / `JFU_WIDTH`)
+    #[serde(default)]
+    pub width: Option<usize>,
+    /// Longest separator line drawn in formatted errors, however wide the terminal
+    #[serde(default = "default_max_width")]
+    pub max_width: usize,
+    #[serde(default)]
+    pub color_scheme: ColorScheme,
+    #[serde(default)]
+    pub compiler: CompilerConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+}
+
+/// Settings forwarded to `javac`. Changing any of them invalidates the build cache.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompilerConfig {
+    /// Classpath entries searched for annotation processors (`-processorpath`)
+    pub processor_path: Vec<PathBuf>,
+    /// Annotation processor class names to run (`-processor`)
+    pub processors: Vec<String>,
+    /// Modular jars/directories for `--module-path` (javac and java)
+    pub module_path: Vec<PathBuf>,
+    /// `-Xlint` categories to enable (e.g. "unchecked"), or disable with a leading `-`
+    pub lint: Vec<String>,
+    /// Java version to compile for (`--release`), or "auto" to pin it to the installed javac
+    pub release: Option<String>,
+    /// Extra javac flags, passed through as-is after jfu's own
+    pub javac_opts: Vec<String>,
+}
+
+/// Shell commands run around builds and runs. A hook that exits non-zero
+/// stops jfu with an error.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Before the dependency graph is built (e.g. code generation)
+    pub pre_build: Option<String>,
+    /// After a successful build, including one where everything was up to date
+    pub post_build: Option<String>,
+    /// After building, right before `jfu run` launches the program
+    pub pre_run: Option<String>,
+}
+
+/// Colors for the diagnostic UI, by role. Values are color names understood by
+/// `colored` (e.g. "red", "bright blue"). `config validate` rejects unknown
+/// names; a build renders them as white.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorScheme {
+    pub error: String,
+    pub warning: String,
+    pub info: String,
+    pub accent: String,
+    pub success: String,
+    /// Syntax highlighting theme for code snippets (see `jfu list themes`)
+    pub theme: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            error: "red".to_string(),
+            warning: "yellow".to_string(),
+            info: "cyan".to_string(),
+            accent: "blue".to_string(),
+            success: "green".to_string(),
+            theme: DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+impl ColorScheme {
+    pub fn error(&self, text: &str) -> ColoredString {
+        text.color(self.error.as_str())
+    }
+
+    pub fn warning(&self, text: &str) -> ColoredString {
+        text.color(self.warning.as_str())
+    }
+
+    pub fn info(&self, text: &str) -> ColoredString {
+        text.color(self.info.as_str())
+    }
+
+    pub fn accent(&self, text: &str) -> ColoredString {
+        text.color(self.accent.as_str())
+    }
+
+    pub fn success(&self, text: &str) -> ColoredString {
+        text.color(self.success.as_str())
+    }
 }
 
 fn default_src_dir() -> PathBuf {
@@ -26,6 +170,18 @@ fn default_out_dir() -> PathBuf {
     PathBuf::from("./out")
 }
 
+fn default_reference_scan_lines() -> usize {
+    20_000
+}
+
+fn default_max_width() -> usize {
+    120
+}
+
+fn default_keep_partial_output() -> bool {
+    true
+}
+
 fn default_cache_file() -> PathBuf {
     PathBuf::from("./jfu-cache.json")
 }
@@ -38,7 +194,24 @@ impl Default for Config {
             cache_file: default_cache_file(),
             jvm_opts: Vec::new(),
             entrypoint: None,
+            entrypoints: BTreeMap::new(),
             auto_include_implicit_deps: false,
+            include_package_private_types: false,
+            reference_scan_lines: default_reference_scan_lines(),
+            prelude_dir: None,
+            resources: Vec::new(),
+            atomic_output: false,
+            keep_partial_output: default_keep_partial_output(),
+            strict_deps: false,
+            fail_on_implicit: false,
+            localized_diagnostics: false,
+            follow_symlinks: false,
+            threads: None,
+            width: None,
+            max_width: default_max_width(),
+            color_scheme: ColorScheme::default(),
+            compiler: CompilerConfig::default(),
+            hooks: HooksConfig::default(),
         }
     }
 }
@@ -67,4 +240,165 @@ impl Config {
 
         Config::default()
     }
+
+    /// Resolves the file to operate on from a command-line argument.
+    ///
+    /// `@name` selects a named entry from `[entrypoints]`; no argument falls back
+    /// to `entrypoint`, then `Main.java`.
+    pub fn resolve_entrypoint(&self, file: Option<String>) -> Result<String, String> {
+        match file {
+            Some(file) => match file.strip_prefix('@') {
+                Some(name) => self.entrypoints.get(name).cloned().ok_or_else(|| {
+                    if self.entrypoints.is_empty() {
+                        format!(
+                            "Unknown entrypoint `@{}` (no [entrypoints] defined in jfu.toml)",
+                            name
+                        )
+                    } else {
+                        let available: Vec<String> = self
+                            .entrypoints
+                            .iter()
+                            .map(|(name, file)| format!("@{} ({})", name, file))
+                            .collect();
+                        format!(
+                            "Unknown entrypoint `@{}`. Available: {}",
+                            name,
+                            available.join(", ")
+                        )
+                    }
+                }),
+                None => self.disambiguate(file),
+            },
+            None => match &self.entrypoint {
+                Some(entrypoint) => Ok(entrypoint.clone()),
+                None => self.detect_entrypoint(),
+            },
+        }
+    }
+
+    /// A bare file name that isn't in the current directory or src_dir could
+    /// mean any same-named main file in a subfolder (e.g. one per exercise),
+    /// so several candidates must be told apart by path
+    fn disambiguate(&self, file: String) -> Result<String, String> {
+        let path = Path::new(&file);
+        let bare = path
+            .parent()
+            .is_none_or(|parent| parent.as_os_str().is_empty());
+        if !bare || path.exists() || self.src_dir.join(path).exists() {
+            return Ok(file);
+        }
+
+        let candidates: Vec<String> = find_main_files_named(self, &file)
+            .iter()
+            .map(|path| {
+                let path = path.strip_prefix(".").unwrap_or(path);
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+        if candidates.len() < 2 {
+            return Ok(file);
+        }
+
+        Err(format!(
+            "`{}` is ambiguous: several files with that name have a main method:\n{}\n   Pass the path of the one you mean (e.g. `jfu run {}`).",
+            file,
+            candidates
+                .iter()
+                .map(|candidate| format!("   → {}", candidate))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            candidates[0]
+        ))
+    }
+
+    /// Falls back to `Main.java`, or else the only source with a `main` method
+    fn detect_entrypoint(&self) -> Result<String, String> {
+        let default = "Main.java".to_string();
+        if Path::new(&default).exists() || self.src_dir.join(&default).exists() {
+            return Ok(default);
+        }
+
+        let mut cache = load_cache(&self.cache_file);
+        let mains = find_main_files(self, &mut cache.main_scans);
+        save_cache(&self.cache_file, &cache);
+
+        match mains.len() {
+            0 => self.disambiguate(default),
+            1 => Ok(mains[0].clone()),
+            _ => Err(format!(
+                "Several files have a main method: {}\n   Pass one explicitly (e.g. `jfu run {}`) or set `entrypoint` in jfu.toml.",
+                mains.join(", "),
+                mains[0]
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_scheme_roles_left_out_keep_their_defaults() {
+        let config: Config =
+            toml::from_str("[color_scheme]\nerror = \"bright red\"\naccent = \"magenta\"\n")
+                .unwrap();
+        let colors = &config.color_scheme;
+
+        assert_eq!(colors.error("x").fgcolor, Some(Color::BrightRed));
+        assert_eq!(colors.accent("x").fgcolor, Some(Color::Magenta));
+        assert_eq!(colors.warning("x").fgcolor, Some(Color::Yellow));
+        assert_eq!(colors.info("x").fgcolor, Some(Color::Cyan));
+        assert_eq!(colors.success("x").fgcolor, Some(Color::Green));
+    }
+
+    #[test]
+    fn unknown_colors_render_as_white() {
+        let colors = ColorScheme {
+            error: "blurple".to_string(),
+            ..ColorScheme::default()
+        };
+        assert_eq!(colors.error("x").fgcolor, Some(Color::White));
+    }
+
+    #[test]
+    fn named_entrypoints_are_selected_with_at() {
+        let config: Config = toml::from_str(
+            "entrypoint = \"Main.java\"\n\
+             [entrypoints]\n\
+             server = \"Server.java\"\n\
+             client = \"tools/Client.java\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.resolve_entrypoint(Some("@server".to_string())),
+            Ok("Server.java".to_string())
+        );
+        assert_eq!(
+            config.resolve_entrypoint(Some("@client".to_string())),
+            Ok("tools/Client.java".to_string())
+        );
+        // The single `entrypoint` still applies when nothing is passed
+        assert_eq!(config.resolve_entrypoint(None), Ok("Main.java".to_string()));
+    }
+
+    #[test]
+    fn unknown_entrypoint_names_list_the_available_ones() {
+        let config: Config =
+            toml::from_str("[entrypoints]\nserver = \"Server.java\"\nclient = \"Client.java\"\n")
+                .unwrap();
+        assert_eq!(
+            config.resolve_entrypoint(Some("@worker".to_string())),
+            Err(
+                "Unknown entrypoint `@worker`. Available: @client (Client.java), @server (Server.java)"
+                    .to_string()
+            )
+        );
+
+        assert_eq!(
+            Config::default().resolve_entrypoint(Some("@worker".to_string())),
+            Err("Unknown entrypoint `@worker` (no [entrypoints] defined in jfu.toml)".to_string())
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use colored::*;
+use std::{fs, path::Path};
+
+use crate::build::{check_release_opts, lint_flag, release_version};
+use crate::config::Config;
+use crate::syntax::theme_names;
+
+/// A config key set on the command line, and the flag that set it
+pub type CliOverride = (&'static str, &'static str);
+
+/// Prints the effective configuration after jfu.toml and CLI flags are applied
+pub fn show_config(
+    config: &Config,
+    json: bool,
+    verbose: bool,
+    overrides: &[CliOverride],
+) -> Result<(), String> {
+    if json {
+        let json = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        println!("{}", json);
+    } else {
+        let toml =
+            toml::to_string(config).map_err(|e| format!("Failed to serialize config: {}", e))?;
+        print!("{}", toml);
+    }
+
+    if verbose {
+        // Keys written in jfu.toml, whether or not they differ from the defaults
+        let file_keys: toml::Table = fs::read_to_string("jfu.toml")
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let value =
+            serde_json::to_value(config).map_err(|e| format!("Failed to inspect config: {}", e))?;
+        let keys = value
+            .as_object()
+            .map(|map| map.keys())
+            .into_iter()
+            .flatten();
+
+        eprintln!("\n{} Sources:", "🔎".cyan());
+        for key in keys {
+            let source = match overrides.iter().find(|(name, _)| name == key) {
+                Some((_, flag)) if flag.starts_with("--") => {
+                    format!("command line ({})", flag).green()
+                }
+                Some((_, var)) => format!("environment ({})", var).green(),
+                None if file_keys.contains_key(key) => "jfu.toml".cyan(),
+                None => "default".bright_black(),
+            };
+            eprintln!("  {:<30} {}", key, source);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks jfu.toml for problems that would otherwise surface as confusing build failures
+pub fn validate_config(config: &Config) -> Result<(), String> {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    match fs::read_to_string("jfu.toml") {
+        Ok(content) => match toml::from_str::<toml::Table>(&content) {
+            Ok(table) => {
+                // serde ignores unknown keys, so typos would silently fall back to defaults
+                let known = serde_json::to_value(Config::default())
+                    .map_err(|e| format!("Failed to inspect config: {}", e))?;
+                for key in table.keys() {
+                    if known.get(key).is_none() {
+                        warnings.push(format!("unknown key `{}` is ignored", key));
+                    }
+                }
+                if let Err(e) = toml::from_str::<Config>(&content) {
+                    errors.push(format!(
+                        "jfu.toml does not match the expected format: {}",
+                        e
+                    ));
+                }
+            }
+            Err(e) => errors.push(format!("jfu.toml is not valid TOML: {}", e)),
+        },
+        Err(_) => warnings.push("no jfu.toml found, checking the defaults".to_string()),
+    }
+
+    if !config.src_dir.is_dir() {
+        errors.push(format!(
+            "src_dir `{}` does not exist",
+            config.src_dir.display()
+        ));
+    }
+
+    // Entrypoints are looked up like the build does: cwd first, then src_dir
+    let entry_exists = |file: &str| Path::new(file).exists() || config.src_dir.join(file).exists();
+    if let Some(entrypoint) = &config.entrypoint
+        && !entry_exists(entrypoint)
+    {
+        errors.push(format!("entrypoint `{}` was not found", entrypoint));
+    }
+    for (name, file) in &config.entrypoints {
+        if !entry_exists(file) {
+            errors.push(format!(
+                "entrypoint `@{}` points to missing `{}`",
+                name, file
+            ));
+        }
+    }
+
+    if let Some(prelude) = &config.prelude_dir
+        && !prelude.is_dir()
+    {
+        errors.push(format!(
+            "prelude_dir `{}` does not exist",
+            prelude.display()
+        ));
+    }
+    for resource in &config.resources {
+        if !resource.exists() {
+            errors.push(format!("resource `{}` does not exist", resource.display()));
+        }
+    }
+    for path in &config.compiler.processor_path {
+        if !path.exists() {
+            warnings.push(format!(
+                "processor_path entry `{}` does not exist",
+                path.display()
+            ));
+        }
+    }
+    if let Err(e) = lint_flag(&config.compiler.lint) {
+        errors.push(e);
+    }
+    if let Err(e) = release_version(config.compiler.release.as_deref(), false) {
+        errors.push(e);
+    }
+    if let Err(e) = check_release_opts(&config.compiler) {
+        errors.push(e);
+    }
+
+    if config.width.is_some_and(|width| width < 40) {
+        errors.push("width must be at least 40 columns".to_string());
+    }
+    if config.max_width < 40 {
+        errors.push("max_width must be at least 40 columns".to_string());
+    }
+
+    let colors = &config.color_scheme;
+    for (role, name) in [
+        ("error", &colors.error),
+        ("warning", &colors.warning),
+        ("info", &colors.info),
+        ("accent", &colors.accent),
+        ("success", &colors.success),
+    ] {
+        if name.parse::<Color>().is_err() {
+            errors.push(format!(
+                "color_scheme.{} `{}` is not a known color (e.g. \"red\", \"bright blue\")",
+                role, name
+            ));
+        }
+    }
+
+    if !theme_names().contains(&colors.theme.as_str()) {
+        errors.push(format!(
+            "color_scheme.theme `{}` is not a known theme (see `jfu list themes`)",
+            colors.theme
+        ));
+    }
+
+    for warning in &warnings {
+        println!("  {} {}", "Warning:".yellow().bold(), warning);
+    }
+    for error in &errors {
+        println!("  {} {}", "Error:".red().bold(), error);
+    }
+
+    if !errors.is_empty() {
+        return Err(format!("Found {} configuration error(s)", errors.len()));
+    }
+
+    println!(
+        "{} Configuration is valid{}",
+        "✓".green(),
+        if warnings.is_empty() {
+            String::new()
+        } else {
+            format!(" ({} warning(s))", warnings.len())
+        }
+    );
+    Ok(())
+}
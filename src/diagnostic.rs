@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// One `file:line: severity: message` report from javac
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    /// 1-based, taken from the caret under the snippet
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+    /// The offending source line as javac printed it (indentation kept)
+    pub snippet: Option<String>,
+    /// Extra detail lines such as `symbol:` and `location:`
+    pub context: Vec<String>,
+}
+
+impl Diagnostic {
+    /// The lint category of a `[category] message` warning, if any
+    pub fn lint_category(&self) -> Option<&str> {
+        self.message
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .map(|(category, _)| category)
+    }
+
+    /// The message without its `[category]` prefix
+    pub fn plain_message(&self) -> &str {
+        match self
+            .message
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+        {
+            Some((_, rest)) => rest.trim(),
+            None => &self.message,
+        }
+    }
+
+    /// A stable rule id for reports: `javac/<lint>` for lint warnings
+    /// (`javac/cast`), otherwise `javac/error`, `javac/warning`, or
+    /// `javac/note`. The specifics stay in the message.
+    pub fn rule_id(&self) -> String {
+        match self.lint_category() {
+            Some(category) => format!("javac/{}", category),
+            None => format!("javac/{:?}", self.severity).to_lowercase(),
+        }
+    }
+}
+
+/// Splits a diagnostic header into its file, line, severity, and message
+fn parse_header(line: &str) -> Option<(&str, usize, Severity, &str)> {
+    let (location, severity, message) = [
+        (": error:", Severity::Error),
+        (": warning:", Severity::Warning),
+        (": note:", Severity::Note),
+    ]
+    .into_iter()
+    .find_map(|(marker, severity)| {
+        line.find(marker)
+            .map(|pos| (&line[..pos], severity, line[pos + marker.len()..].trim()))
+    })?;
+
+    // Anchor on `.java:` rather than the last colon, so drive letters in
+    // Windows paths (`C:\src\Foo.java:10`) stay part of the file
+    let java_pos = location.rfind(".java:")?;
+    let file = &location[..java_pos + ".java".len()];
+    let line = location[java_pos + ".java:".len()..].parse().ok()?;
+
+    Some((file, line, severity, message))
+}
+
+/// Summary lines like "1 error" or "3 warnings"
+pub fn is_summary_line(line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    matches!(
+        (words.next(), words.next(), words.next()),
+        (Some(count), Some("error" | "errors" | "warning" | "warnings"), None)
+            if count.chars().all(|c| c.is_ascii_digit())
+    )
+}
+
+/// Parses javac's stdout/stderr into structured diagnostics, in output order
+pub fn parse_javac_output(text: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut diagnostics = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let Some((file, line, severity, message)) = parse_header(lines[i].trim()) else {
+            i += 1;
+            continue;
+        };
+        i += 1;
+
+        let ends_block = |line: &str| {
+            let trimmed = line.trim();
+            trimmed.is_empty()
+                || parse_header(trimmed).is_some()
+                || is_summary_line(trimmed)
+                || trimmed.starts_with("Note:")
+        };
+
+        // The offending line, then a caret marking the column
+        let mut snippet = None;
+        let mut column = None;
+        if i < lines.len() && !ends_block(lines[i]) && !lines[i].trim().starts_with('^') {
+            snippet = Some(lines[i].to_string());
+            i += 1;
+        }
+        if i < lines.len() && lines[i].trim_start().starts_with('^') {
+            column = Some(lines[i].chars().take_while(|c| c.is_whitespace()).count() + 1);
+            i += 1;
+        }
+
+        let mut context = Vec::new();
+        while i < lines.len() && !ends_block(lines[i]) {
+            context.push(lines[i].trim().to_string());
+            i += 1;
+        }
+
+        diagnostics.push(Diagnostic {
+            file: PathBuf::from(file),
+            line,
+            column,
+            severity,
+            message: message.to_string(),
+            snippet,
+            context,
+        });
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from javac 17
+    const TWO_ERRORS: &str = "\
+Broken.java:3: error: incompatible types: String cannot be converted to int
+        int x = \"text\";
+                ^
+Broken.java:4: error: cannot find symbol
+        undefined();
+        ^
+  symbol:   method undefined()
+  location: class Broken
+2 errors
+";
+
+    const WARNING_WITH_NOTES: &str = "\
+Lint.java:8: warning: [cast] redundant cast to int
+        int i = (int) 3;
+                ^
+Note: Lint.java uses unchecked or unsafe operations.
+Note: Recompile with -Xlint:unchecked for details.
+1 warning
+";
+
+    #[test]
+    fn parses_each_error_with_its_snippet_and_context() {
+        let diagnostics = parse_javac_output(TWO_ERRORS);
+        assert_eq!(diagnostics.len(), 2);
+
+        let first = &diagnostics[0];
+        assert_eq!(first.file, PathBuf::from("Broken.java"));
+        assert_eq!(first.line, 3);
+        assert_eq!(first.severity, Severity::Error);
+        assert_eq!(
+            first.message,
+            "incompatible types: String cannot be converted to int"
+        );
+        assert_eq!(first.snippet.as_deref(), Some("        int x = \"text\";"));
+        assert!(first.context.is_empty());
+
+        assert_eq!(first.rule_id(), "javac/error");
+
+        let second = &diagnostics[1];
+        assert_eq!(second.line, 4);
+        assert_eq!(second.message, "cannot find symbol");
+        assert_eq!(
+            second.context,
+            ["symbol:   method undefined()", "location: class Broken"]
+        );
+    }
+
+    #[test]
+    fn takes_the_column_from_the_caret() {
+        let diagnostics = parse_javac_output(TWO_ERRORS);
+        assert_eq!(diagnostics[0].column, Some(17));
+        assert_eq!(diagnostics[1].column, Some(9));
+    }
+
+    #[test]
+    fn parses_lint_warnings_and_skips_notes() {
+        let diagnostics = parse_javac_output(WARNING_WITH_NOTES);
+        assert_eq!(diagnostics.len(), 1);
+
+        let warning = &diagnostics[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.column, Some(17));
+        assert_eq!(warning.lint_category(), Some("cast"));
+        assert_eq!(warning.plain_message(), "redundant cast to int");
+        assert_eq!(warning.rule_id(), "javac/cast");
+        // The trailing `Note:` lines aren't context of the warning
+        assert!(warning.context.is_empty());
+    }
+
+    #[test]
+    fn parses_note_headers_without_a_snippet() {
+        let diagnostics = parse_javac_output(
+            "Gen.java:12: note: generated by an annotation processor\n1 warning\n",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Note);
+        assert_eq!(diagnostics[0].snippet, None);
+        assert_eq!(diagnostics[0].column, None);
+    }
+
+    #[test]
+    fn keeps_drive_letters_in_windows_paths() {
+        let diagnostics = parse_javac_output(
+            "C:\\src\\Main.java:7: error: ';' expected\n        int x = 1\n                 ^\n1 error\n",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, PathBuf::from("C:\\src\\Main.java"));
+        assert_eq!(diagnostics[0].line, 7);
+        assert_eq!(diagnostics[0].column, Some(18));
+    }
+}
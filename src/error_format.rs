@@ -1,10 +1,18 @@
 use colored::*;
+use std::collections::BTreeMap;
 use terminal_size::{Width, terminal_size};
 
+use crate::config::{ColorScheme, Config};
+use crate::diagnostic::{Diagnostic, Severity, is_summary_line, parse_javac_output};
 use crate::syntax::highlight_java_code;
 
-/// Get the current terminal width, defaulting to 80 if unable to detect
-fn get_terminal_width() -> usize {
+/// The width to lay output out for: `width` from jfu.toml (or `--width` /
+/// `JFU_WIDTH`) when set, otherwise the detected terminal width (80 if it
+/// can't be detected)
+fn get_terminal_width(config: &Config) -> usize {
+    if let Some(width) = config.width {
+        return width;
+    }
     if let Some((Width(w), _)) = terminal_size() {
         w as usize
     } else {
@@ -13,137 +21,267 @@ fn get_terminal_width() -> usize {
 }
 
 /// Create a separator line that fits the terminal width
-fn separator(width: usize) -> String {
-    "─".repeat(width.min(120)) // Cap at 120 for very wide terminals
+fn separator(width: usize, max_width: usize) -> String {
+    "─".repeat(width.min(max_width)) // Cap for very wide terminals
 }
 
-pub fn format_java_errors(error_text: &str) -> String {
-    let term_width = get_terminal_width();
-    let sep_width = (term_width - 2).max(40); // Leave some margin
+pub fn format_java_errors(error_text: &str, config: &Config) -> String {
+    let colors = &config.color_scheme;
+    let term_width = get_terminal_width(config);
+    let sep_width = term_width.saturating_sub(2).max(40); // Leave some margin
 
     let mut formatted = String::new();
     formatted.push_str(&format!(
         "\n{} {}\n",
-        "💥".red(),
-        "Compilation Failed".red().bold()
+        colors.error("💥"),
+        colors.error("Compilation Failed").bold()
     ));
 
-    let lines: Vec<&str> = error_text.lines().collect();
-    let mut i = 0;
-    let mut error_count = 0;
+    let errors: Vec<Diagnostic> = parse_javac_output(error_text)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .collect();
 
-    while i < lines.len() {
-        let line = lines[i].trim();
+    for (index, error) in errors.iter().enumerate() {
+        formatted.push_str(&format!(
+            "\n{} {}\n",
+            colors.warning(&format!("Error #{}", index + 1)).bold(),
+            colors.warning(&separator(sep_width - 12, config.max_width)) // Subtract space for "Error #N "
+        ));
 
-        // Check if this is an error line (typically starts with file path)
-        if line.contains(".java:") && line.contains(": error:") {
-            error_count += 1;
+        formatted.push_str(&format!(
+            "  {} {}\n",
+            colors.info("📄"),
+            colors.info(&error.file.to_string_lossy())
+        ));
+        formatted.push_str(&format!(
+            "  {} Line {}\n",
+            colors.warning("📍"),
+            colors.warning(&error.line.to_string()).bold()
+        ));
+        formatted.push_str(&format!(
+            "  {} {}\n",
+            colors.error("💬"),
+            error.message.white()
+        ));
 
-            // Parse the error line: ./test/File.java:10: error: message
-            if let Some(colon_pos) = line.find(": error:") {
-                let file_and_line = &line[..colon_pos];
-                let error_msg = &line[colon_pos + 8..].trim();
+        // Show the problematic code line
+        if let Some(code_line) = &error.snippet {
+            let trimmed = code_line.trim();
+            // Preserve leading whitespace for alignment
+            let leading_spaces = code_line.chars().count() - code_line.trim_start().chars().count();
+            let highlighted_code = highlight_java_code(trimmed, &colors.theme);
+            formatted.push_str(&format!("\n  {}\n", highlighted_code));
+
+            // Show the caret indicator, shifted by the indentation we removed
+            if let Some(column) = error.column {
+                let offset = (column - 1).saturating_sub(leading_spaces);
+                let aligned_caret = format!("{}^", " ".repeat(offset));
+                formatted.push_str(&format!("  {}\n", colors.error(&aligned_caret).bold()));
+            }
+        }
 
+        // Show additional context lines (symbol, location info)
+        for context_line in &error.context {
+            if context_line.starts_with("symbol:") || context_line.starts_with("location:") {
                 formatted.push_str(&format!(
-                    "\n{} {}\n",
-                    format!("Error #{}", error_count).yellow().bold(),
-                    separator(sep_width - 12).yellow() // Subtract space for "Error #N "
+                    "    {} {}\n",
+                    colors.accent("•"),
+                    context_line.bright_black()
                 ));
-
-                // Extract file and line number
-                if let Some(last_colon) = file_and_line.rfind(':') {
-                    let location = &file_and_line[last_colon + 1..];
-                    let file_path = &file_and_line[..last_colon];
-
-                    formatted.push_str(&format!("  {} {}\n", "📄".cyan(), file_path.cyan()));
-                    formatted.push_str(&format!(
-                        "  {} Line {}\n",
-                        "📍".yellow(),
-                        location.yellow().bold()
-                    ));
-                    formatted.push_str(&format!("  {} {}\n", "💬".red(), error_msg.white()));
-                }
-
-                // Show the problematic code line (next line usually)
-                if i + 1 < lines.len() {
-                    let code_line = lines[i + 1];
-                    let trimmed = code_line.trim();
-                    if !trimmed.is_empty() && !trimmed.starts_with("^") {
-                        // Preserve leading whitespace for alignment
-                        let leading_spaces = code_line.len() - code_line.trim_start().len();
-                        let highlighted_code = highlight_java_code(trimmed);
-                        formatted.push_str(&format!("\n  {}\n", highlighted_code));
-
-                        // Show the caret indicator (line after code) with proper alignment
-                        if i + 2 < lines.len() {
-                            let caret_line = lines[i + 2];
-                            let caret_trimmed = caret_line.trim_start();
-                            if caret_trimmed.starts_with("^") {
-                                // Calculate the offset: original leading spaces minus what we removed
-                                let caret_spaces = caret_line.len() - caret_line.trim_start().len();
-                                let offset = if caret_spaces > leading_spaces {
-                                    caret_spaces - leading_spaces
-                                } else {
-                                    0
-                                };
-                                let aligned_caret =
-                                    format!("{}{}", " ".repeat(offset), caret_trimmed);
-                                formatted.push_str(&format!("  {}\n", aligned_caret.red().bold()));
-                            }
-                        }
-                    }
-                }
-
-                // Show additional context lines (symbol, location info)
-                let mut j = i + 3;
-                while j < lines.len() && j < i + 10 {
-                    let context_line = lines[j].trim();
-                    if context_line.is_empty() {
-                        break;
-                    }
-                    if context_line.starts_with("symbol:") || context_line.starts_with("location:")
-                    {
-                        formatted.push_str(&format!(
-                            "    {} {}\n",
-                            "•".blue(),
-                            context_line.bright_black()
-                        ));
-                    } else if !context_line.contains(".java:") {
-                        formatted.push_str(&format!("    {}\n", context_line.bright_black()));
-                    } else {
-                        break;
-                    }
-                    j += 1;
-                }
+            } else {
+                formatted.push_str(&format!("    {}\n", context_line.bright_black()));
             }
-        } else if line.contains(" error") && line.ends_with(" error") {
-            // Summary line like "1 error" or "3 errors"
-            formatted.push_str(&format!("\n{}\n", separator(sep_width).yellow()));
-            formatted.push_str(&format!("{} {}\n", "📊".yellow(), line.red().bold()));
         }
 
-        i += 1;
+        if let Some(hint) = checked_exception_hint(error, colors) {
+            formatted.push_str(&hint);
+        }
+    }
+
+    // Summary line like "1 error" or "3 errors"
+    if let Some(summary) = error_text
+        .lines()
+        .map(str::trim)
+        .find(|line| is_summary_line(line) && line.contains(" error"))
+    {
+        formatted.push_str(&format!(
+            "\n{}\n",
+            colors.warning(&separator(sep_width, config.max_width))
+        ));
+        formatted.push_str(&format!(
+            "{} {}\n",
+            colors.warning("📊"),
+            colors.error(summary).bold()
+        ));
     }
 
-    if error_count == 0 {
+    if errors.is_empty() {
         // Fallback if we couldn't parse the error format
-        formatted.push_str("\n");
+        formatted.push('\n');
         for line in error_text.lines() {
-            formatted.push_str(&format!("  {}\n", line.red()));
+            formatted.push_str(&format!("  {}\n", colors.error(line)));
         }
     } else {
         formatted.push_str(&format!(
             "\n{} Fix the errors above and try again.\n",
-            "💡".cyan()
+            colors.info("💡")
         ));
     }
 
     formatted
 }
 
-pub fn format_runtime_errors(error_text: &str) -> String {
-    let term_width = get_terminal_width();
-    let sep_width = (term_width - 2).max(40); // Leave some margin
+/// For `unreported exception X; must be caught or declared to be thrown`,
+/// sketches both fixes: catching X around the offending line, or declaring it
+fn checked_exception_hint(error: &Diagnostic, colors: &ColorScheme) -> Option<String> {
+    let exception = error
+        .message
+        .strip_prefix("unreported exception ")?
+        .split(';')
+        .next()?
+        .trim();
+    let simple_name = exception.rsplit('.').next().unwrap_or(exception);
+    let statement = error
+        .snippet
+        .as_deref()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .unwrap_or("// the call that throws");
+
+    let mut hint = format!(
+        "\n  {} `{}` is a checked exception, so it must be caught or declared. Either catch it:\n",
+        colors.info("💡"),
+        simple_name
+    );
+    // Highlighted as one block so the braces are matched up
+    let indent = |code: String| -> String {
+        highlight_java_code(&code, &colors.theme)
+            .lines()
+            .map(|line| format!("      {}\n", line))
+            .collect()
+    };
+    hint.push_str(&indent(format!(
+        "try {{\n    {}\n}} catch ({} e) {{\n    // handle it, e.g. e.printStackTrace();\n}}",
+        statement, simple_name
+    )));
+    hint.push_str("    or declare it on the enclosing method:\n");
+    hint.push_str(&indent(format!(
+        "void yourMethod() throws {} {{ ... }}",
+        simple_name
+    )));
+    Some(hint)
+}
+
+/// Formats javac warnings grouped by their lint category (e.g. `[unchecked]`)
+pub fn format_java_warnings(warning_text: &str, colors: &ColorScheme) -> String {
+    // Without -Xlint, javac only summarizes unchecked/deprecated usage in notes
+    let notes: Vec<&str> = java_notes(warning_text).collect();
+    format_warnings(&parse_javac_output(warning_text), &notes, "", colors)
+}
+
+/// Formats warnings recorded when now up-to-date files were last compiled
+pub fn format_cached_warnings(warnings: &[Diagnostic], colors: &ColorScheme) -> String {
+    format_warnings(
+        warnings,
+        &[],
+        " in up-to-date files (from their last compile)",
+        colors,
+    )
+}
+
+fn format_warnings(
+    warnings: &[Diagnostic],
+    notes: &[&str],
+    label: &str,
+    colors: &ColorScheme,
+) -> String {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut warning_count = 0;
+
+    for warning in warnings {
+        if warning.severity != Severity::Warning {
+            continue;
+        }
+        warning_count += 1;
+
+        // Categorized warnings look like `[unchecked] unchecked call to ...`
+        let category = warning.lint_category().unwrap_or("other").to_string();
+        let message = warning.plain_message();
+
+        let mut entry = format!(
+            "    {} {}\n      {} {}\n",
+            colors.info("📄"),
+            colors.info(&format!("{}:{}", warning.file.display(), warning.line)),
+            colors.warning("💬"),
+            message.white()
+        );
+        if let Some(code_line) = &warning.snippet {
+            let leading_spaces = code_line.chars().count() - code_line.trim_start().chars().count();
+            entry.push_str(&format!(
+                "      {}\n",
+                highlight_java_code(code_line.trim(), &colors.theme)
+            ));
+            if let Some(column) = warning.column {
+                let offset = (column - 1).saturating_sub(leading_spaces);
+                let aligned_caret = format!("{}^", " ".repeat(offset));
+                entry.push_str(&format!(
+                    "      {}\n",
+                    colors.warning(&aligned_caret).bold()
+                ));
+            }
+        }
+        groups.entry(category).or_default().push(entry);
+    }
+
+    let mut formatted = String::new();
+    if warning_count > 0 {
+        formatted.push_str(&format!(
+            "\n{} {}\n",
+            colors.warning("⚠️"),
+            colors
+                .warning(&format!("{} warning(s){}", warning_count, label))
+                .bold()
+        ));
+    }
+    for (category, entries) in &groups {
+        formatted.push_str(&format!(
+            "\n  {} {}\n",
+            colors.warning(&format!("[{}]", category)).bold(),
+            format!("({})", entries.len()).bright_black()
+        ));
+        for entry in entries {
+            formatted.push_str(entry);
+        }
+    }
+    if !notes.is_empty() {
+        formatted.push('\n');
+        for note in notes {
+            formatted.push_str(&format!("{} {}\n", colors.info("ℹ️"), note.bright_black()));
+        }
+    }
+    formatted.push('\n');
+
+    formatted
+}
+
+/// javac's summary notes (`Note: Main.java uses unchecked or unsafe operations.`)
+fn java_notes(output: &str) -> impl Iterator<Item = &str> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("Note:"))
+}
+
+/// Whether javac output from a successful compile has anything worth showing
+pub fn has_java_warnings(output: &str) -> bool {
+    output.contains(": warning:") || java_notes(output).next().is_some()
+}
+
+pub fn format_runtime_errors(error_text: &str, config: &Config) -> String {
+    let colors = &config.color_scheme;
+    let term_width = get_terminal_width(config);
+    let sep_width = term_width.saturating_sub(2).max(40); // Leave some margin
 
     let lines: Vec<&str> = error_text.lines().collect();
 
@@ -152,17 +290,20 @@ pub fn format_runtime_errors(error_text: &str) -> String {
         let mut formatted = String::new();
         formatted.push_str(&format!(
             "\n{} {}\n",
-            "🔄".red(),
-            "Stack Overflow Error - Infinite Recursion Detected!"
-                .red()
+            colors.error("🔄"),
+            colors
+                .error("Stack Overflow Error - Infinite Recursion Detected!")
                 .bold()
         ));
-        formatted.push_str(&format!("{}\n", separator(sep_width).red()));
+        formatted.push_str(&format!(
+            "{}\n",
+            colors.error(&separator(sep_width, config.max_width))
+        ));
 
         formatted.push_str(&format!(
             "\n  {} {}\n",
-            "💡".yellow(),
-            "This usually happens when:".yellow().bold()
+            colors.warning("💡"),
+            colors.warning("This usually happens when:").bold()
         ));
         formatted.push_str("    • A method calls itself without a proper base case\n");
         formatted.push_str("    • Methods call each other in a circular pattern\n");
@@ -179,14 +320,14 @@ pub fn format_runtime_errors(error_text: &str) -> String {
         if !at_lines.is_empty() {
             formatted.push_str(&format!(
                 "  {} {}\n\n",
-                "📍".cyan(),
-                "Top of call stack (most recent calls):".cyan().bold()
+                colors.info("📍"),
+                colors.info("Top of call stack (most recent calls):").bold()
             ));
 
             for (i, line) in at_lines.iter().enumerate() {
                 let trimmed = line.trim();
                 if trimmed.contains(".java:") {
-                    formatted.push_str(&format!("    {}. {}\n", i + 1, trimmed.cyan()));
+                    formatted.push_str(&format!("    {}. {}\n", i + 1, colors.info(trimmed)));
                 } else {
                     formatted.push_str(&format!("    {}. {}\n", i + 1, trimmed.bright_black()));
                 }
@@ -201,17 +342,80 @@ pub fn format_runtime_errors(error_text: &str) -> String {
             if total_at_lines > 10 {
                 formatted.push_str(&format!(
                     "\n    {} ... and {} more recursive calls\n",
-                    "↓".yellow(),
+                    colors.warning("↓"),
                     total_at_lines - 10
                 ));
             }
         }
 
-        formatted.push_str(&format!("\n{}\n", separator(sep_width).red()));
+        formatted.push_str(&format!(
+            "\n{}\n",
+            colors.error(&separator(sep_width, config.max_width))
+        ));
         formatted.push_str(&format!(
             "{} {} to prevent infinite recursion.\n",
-            "🔧".green(),
-            "Add a base case or exit condition".green().bold()
+            colors.success("🔧"),
+            colors.success("Add a base case or exit condition").bold()
+        ));
+
+        return formatted;
+    }
+
+    // Check for OutOfMemoryError (huge or endlessly growing data structures)
+    if let Some(oom_line) = lines.iter().find(|line| line.contains("OutOfMemoryError")) {
+        let mut formatted = String::new();
+        formatted.push_str(&format!(
+            "\n{} {}\n",
+            colors.error("🧠"),
+            colors
+                .error("Out of Memory Error - The JVM Ran Out of Heap!")
+                .bold()
+        ));
+        formatted.push_str(&format!(
+            "{}\n",
+            colors.error(&separator(sep_width, config.max_width))
+        ));
+
+        formatted.push_str(&format!(
+            "\n  {} {}\n",
+            colors.warning("🔥"),
+            oom_line.trim()
+        ));
+
+        formatted.push_str(&format!(
+            "\n  {} {}\n",
+            colors.warning("💡"),
+            colors.warning("This usually happens when:").bold()
+        ));
+        if oom_line.contains("GC overhead limit") {
+            formatted.push_str(
+                "    • The program keeps almost all of its objects alive, so GC can't free anything\n",
+            );
+        }
+        formatted.push_str("    • A loop keeps adding to a list or map that never shrinks\n");
+        formatted.push_str("    • An array or collection is created with a huge size\n");
+        formatted.push_str(
+            "    • Objects are kept around (e.g. in a static field) long after they're needed\n",
+        );
+
+        if let Some(frame) = first_user_frame(&lines) {
+            formatted.push_str(&format!(
+                "\n  {} {}\n    {} {}\n",
+                colors.info("📍"),
+                colors.info("Memory ran out in your code at:").bold(),
+                colors.info("→"),
+                colors.info(frame)
+            ));
+        }
+
+        formatted.push_str(&format!(
+            "\n{}\n",
+            colors.error(&separator(sep_width, config.max_width))
+        ));
+        formatted.push_str(&format!(
+            "{} If the program really needs more memory, raise the heap limit in jfu.toml:\n   {}\n",
+            colors.success("🔧"),
+            colors.success("jvm_opts = [\"-Xmx1g\"]").bold()
         ));
 
         return formatted;
@@ -222,23 +426,34 @@ pub fn format_runtime_errors(error_text: &str) -> String {
         let mut formatted = String::new();
         formatted.push_str(&format!(
             "{} {}\n",
-            "💥".red(),
-            "Runtime Error".red().bold()
+            colors.error("💥"),
+            colors.error("Runtime Error").bold()
+        ));
+        formatted.push_str(&format!(
+            "{}\n",
+            colors.error(&separator(sep_width, config.max_width))
         ));
-        formatted.push_str(&format!("{}\n", separator(sep_width).red()));
 
         for (i, line) in lines.iter().enumerate() {
             let trimmed = line.trim();
 
             // Exception type line
             if trimmed.contains("Exception") && i == 0 {
-                formatted.push_str(&format!("\n  {} {}\n", "🔥".yellow(), trimmed.red().bold()));
+                formatted.push_str(&format!(
+                    "\n  {} {}\n",
+                    colors.warning("🔥"),
+                    colors.error(trimmed).bold()
+                ));
             }
             // Stack trace lines
             else if trimmed.starts_with("at ") {
                 // Highlight our code vs library code
                 if trimmed.contains(".java:") {
-                    formatted.push_str(&format!("    {} {}\n", "→".cyan(), trimmed.cyan()));
+                    formatted.push_str(&format!(
+                        "    {} {}\n",
+                        colors.info("→"),
+                        colors.info(trimmed)
+                    ));
                 } else {
                     formatted.push_str(&format!(
                         "    {} {}\n",
@@ -249,18 +464,25 @@ pub fn format_runtime_errors(error_text: &str) -> String {
             }
             // Caused by
             else if trimmed.starts_with("Caused by:") {
-                formatted.push_str(&format!("\n  {} {}\n", "↳".yellow(), trimmed.yellow()));
+                formatted.push_str(&format!(
+                    "\n  {} {}\n",
+                    colors.warning("↳"),
+                    colors.warning(trimmed)
+                ));
             }
             // Other lines
             else if !trimmed.is_empty() {
-                formatted.push_str(&format!("  {}\n", trimmed.red()));
+                formatted.push_str(&format!("  {}\n", colors.error(trimmed)));
             }
         }
 
-        formatted.push_str(&format!("\n{}\n", separator(sep_width).red()));
+        formatted.push_str(&format!(
+            "\n{}\n",
+            colors.error(&separator(sep_width, config.max_width))
+        ));
         formatted.push_str(&format!(
             "{} Check the stack trace above to find the issue.\n",
-            "💡".cyan()
+            colors.info("💡")
         ));
 
         formatted
@@ -268,9 +490,123 @@ pub fn format_runtime_errors(error_text: &str) -> String {
         // Not a standard exception, return as-is but colored
         format!(
             "{} {}\n{}",
-            "⚠️".yellow(),
-            "Error:".yellow().bold(),
-            error_text.red()
+            colors.warning("⚠️"),
+            colors.warning("Error:").bold(),
+            colors.error(error_text)
         )
     }
 }
+
+/// Finds the first stack frame that isn't inside the JDK
+fn first_user_frame<'a>(lines: &[&'a str]) -> Option<&'a str> {
+    lines
+        .iter()
+        .map(|line| line.trim())
+        .filter_map(|line| line.strip_prefix("at "))
+        .find(|frame| {
+            // JDK frames carry a module prefix like `java.base/`
+            !frame.contains('/')
+                && !["java.", "javax.", "jdk.", "sun."]
+                    .iter()
+                    .any(|prefix| frame.starts_with(prefix))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warnings_are_grouped_by_lint_category() {
+        colored::control::set_override(false);
+        let output = "\
+A.java:3: warning: [cast] redundant cast to int
+        int i = (int) 3;
+                ^
+B.java:5: warning: [unchecked] unchecked conversion
+        List<String> list = new ArrayList();
+                            ^
+B.java:9: warning: [cast] redundant cast to long
+        long l = (long) 4L;
+                 ^
+Note: Some input files use unchecked or unsafe operations.
+3 warnings
+";
+        let formatted = format_java_warnings(output, &ColorScheme::default());
+
+        assert!(formatted.contains("3 warning(s)"));
+        let cast = formatted.find("[cast] (2)").unwrap();
+        let unchecked = formatted.find("[unchecked] (1)").unwrap();
+        assert!(cast < unchecked);
+        // Each entry sits under its category, with the prefix stripped
+        let cast_entries = &formatted[cast..unchecked];
+        assert!(cast_entries.contains("A.java:3"));
+        assert!(cast_entries.contains("B.java:9"));
+        assert!(cast_entries.contains("redundant cast to long"));
+        assert!(!cast_entries.contains("[cast] redundant"));
+        assert!(formatted[unchecked..].contains("B.java:5"));
+        assert!(formatted.contains("Note: Some input files use unchecked or unsafe operations."));
+    }
+
+    #[test]
+    fn out_of_memory_points_at_user_code_and_suggests_xmx() {
+        colored::control::set_override(false);
+        let trace = "\
+Exception in thread \"main\" java.lang.OutOfMemoryError: Java heap space
+\tat java.base/java.util.Arrays.copyOf(Arrays.java:3512)
+\tat java.base/java.util.ArrayList.grow(ArrayList.java:237)
+\tat java.base/java.util.ArrayList.add(ArrayList.java:455)
+\tat Hoarder.fill(Hoarder.java:8)
+\tat Main.main(Main.java:4)
+";
+        let formatted = format_runtime_errors(trace, &Config::default());
+
+        assert!(formatted.contains("Out of Memory Error"));
+        assert!(formatted.contains("java.lang.OutOfMemoryError: Java heap space"));
+        assert!(formatted.contains("→ Hoarder.fill(Hoarder.java:8)"));
+        assert!(formatted.contains("jvm_opts = [\"-Xmx1g\"]"));
+        assert!(!formatted.contains("GC can't free anything"));
+        assert!(!formatted.contains("Runtime Error"));
+    }
+
+    #[test]
+    fn gc_overhead_limit_is_explained_as_out_of_memory() {
+        colored::control::set_override(false);
+        let trace = "\
+Exception in thread \"main\" java.lang.OutOfMemoryError: GC overhead limit exceeded
+\tat Main.main(Main.java:6)
+";
+        let formatted = format_runtime_errors(trace, &Config::default());
+
+        assert!(formatted.contains("Out of Memory Error"));
+        assert!(formatted.contains("GC can't free anything"));
+        assert!(formatted.contains("→ Main.main(Main.java:6)"));
+    }
+
+    #[test]
+    fn unreported_exception_sketches_catching_and_declaring_it() {
+        colored::control::set_override(false);
+        let output = "\
+Main.java:5: error: unreported exception java.io.IOException; must be caught or declared to be thrown
+        reader.readLine();
+                       ^
+1 error
+";
+        let formatted = format_java_errors(output, &Config::default());
+        // Snippets are syntax-highlighted whatever the color setting
+        let plain = regex::Regex::new(r"\x1b\[[0-9;]*m")
+            .unwrap()
+            .replace_all(&formatted, "")
+            .to_string();
+
+        assert!(
+            plain.contains("`IOException` is a checked exception"),
+            "{plain}"
+        );
+        assert!(
+            plain.contains("try {\n          reader.readLine();\n      } catch (IOException e) {")
+        );
+        assert!(plain.contains("or declare it on the enclosing method:"));
+        assert!(plain.contains("void yourMethod() throws IOException { ... }"));
+    }
+}
@@ -1,22 +1,258 @@
 use colored::*;
+use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
 use std::{
+    cell::RefCell,
     collections::{HashMap, HashSet},
     fs,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
 };
 
+use crate::cache::{GraphCache, MainScan, ParsedSource, compute_fingerprint, source_stamp};
+use crate::config::Config;
+
+lazy_static! {
+    // Matches: public class, public interface, public enum, public record, public @interface,
+    // with any of abstract/final/sealed/non-sealed/strictfp/static after `public`
+    static ref PUBLIC_TYPE_REGEX: Regex = Regex::new(
+        r"(?m)^\s*public\s+(?:(?:abstract|final|sealed|non-sealed|strictfp|static)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+    )
+    .unwrap();
+    // Matches: class, interface, enum, record, @interface (with or without modifiers)
+    static ref TYPE_DECL_REGEX: Regex = Regex::new(
+        r"(?m)^\s*(?:(?:public|protected|private|abstract|final|sealed|non-sealed|strictfp|static)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+    )
+    .unwrap();
+    // Matches patterns like: new ClassName(), ClassName variable, ClassName.method()
+    static ref CLASS_REF_REGEX: Regex = Regex::new(r"\b([A-Z][a-zA-Z0-9_]*)\b").unwrap();
+    // Matches top-level (unindented) types of any visibility: class, final class,
+    // abstract class, interface, enum, record, ...
+    static ref TOP_LEVEL_TYPE_REGEX: Regex = Regex::new(
+        r"(?m)^(?:(?:public|abstract|final|sealed|non-sealed|strictfp)\s+)*(?:class|interface|enum|record|@interface)\s+(\w+)",
+    )
+    .unwrap();
+    // Matches: void main(String[] args), void main(final String... args), void main()
+    static ref MAIN_METHOD_REGEX: Regex =
+        Regex::new(r"\bvoid\s+main\s*\(\s*(?:\)|(?:final\s+)?String\b)").unwrap();
+    // Matches: module com.example.app {  /  open module com.example.app {
+    static ref MODULE_REGEX: Regex =
+        Regex::new(r"(?m)^\s*(?:open\s+)?module\s+([\w.]+)\s*\{").unwrap();
+    // Matches: package com.example.app;
+    static ref PACKAGE_REGEX: Regex =
+        Regex::new(r"(?m)^\s*package\s+([\w.]+)\s*;").unwrap();
+}
+
+lazy_static! {
+    // Type name (simple and package-qualified) -> declaring file, built on first
+    // use after each graph build starts
+    static ref CLASS_INDEX: Mutex<Option<HashMap<String, PathBuf>>> = Mutex::new(None);
+    // Files already reported as unreadable or non-UTF-8, so each is warned about once
+    static ref WARNED_SOURCES: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+fn warn_once(path: &Path, message: &str) {
+    let mut warned = WARNED_SOURCES.lock().unwrap_or_else(|e| e.into_inner());
+    if warned.insert(path.to_path_buf()) {
+        eprintln!(
+            "     {} `{}` {}",
+            "Warning:".yellow().bold(),
+            path.display(),
+            message
+        );
+    }
+}
+
+/// Reads a source file for analysis. Files that aren't valid UTF-8 (usually
+/// Latin-1 / Windows-1252 sources) are decoded byte-per-char instead of being
+/// dropped, which keeps every ASCII identifier intact.
+pub fn read_source(path: &Path) -> Option<String> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn_once(path, &format!("could not be read ({}), skipping it", e));
+            return None;
+        }
+    };
+
+    match String::from_utf8(bytes) {
+        Ok(content) => Some(content),
+        Err(e) => {
+            warn_once(
+                path,
+                "is not valid UTF-8 (it looks like Latin-1/Windows-1252); analyzing it anyway. Save it as UTF-8 to avoid javac encoding errors",
+            );
+            Some(e.into_bytes().iter().map(|&byte| byte as char).collect())
+        }
+    }
+}
+
+/// Frequently used standard-library classes that a local type can accidentally shadow
+const WELL_KNOWN_CLASSES: &[(&str, &str)] = &[
+    ("Boolean", "java.lang"),
+    ("Character", "java.lang"),
+    ("Double", "java.lang"),
+    ("Exception", "java.lang"),
+    ("Float", "java.lang"),
+    ("Integer", "java.lang"),
+    ("Iterable", "java.lang"),
+    ("Long", "java.lang"),
+    ("Math", "java.lang"),
+    ("Number", "java.lang"),
+    ("Object", "java.lang"),
+    ("Record", "java.lang"),
+    ("Runnable", "java.lang"),
+    ("RuntimeException", "java.lang"),
+    ("Short", "java.lang"),
+    ("String", "java.lang"),
+    ("StringBuilder", "java.lang"),
+    ("System", "java.lang"),
+    ("Thread", "java.lang"),
+    ("ArrayList", "java.util"),
+    ("Arrays", "java.util"),
+    ("Collection", "java.util"),
+    ("Collections", "java.util"),
+    ("Date", "java.util"),
+    ("Deque", "java.util"),
+    ("HashMap", "java.util"),
+    ("HashSet", "java.util"),
+    ("Iterator", "java.util"),
+    ("LinkedList", "java.util"),
+    ("List", "java.util"),
+    ("Map", "java.util"),
+    ("Objects", "java.util"),
+    ("Optional", "java.util"),
+    ("Queue", "java.util"),
+    ("Random", "java.util"),
+    ("Scanner", "java.util"),
+    ("Set", "java.util"),
+    ("Stack", "java.util"),
+    ("TreeMap", "java.util"),
+    ("TreeSet", "java.util"),
+];
+
+/// Finds types declared in the graph's files that shadow well-known JDK classes
+pub fn find_shadowed_stdlib(graph: &HashMap<String, Node>) -> Vec<GraphWarning> {
+    let mut names: Vec<&String> = graph.keys().collect();
+    names.sort();
+
+    let mut shadowed = Vec::new();
+    for name in names {
+        let Some(content) = read_source(&graph[name].path) else {
+            continue;
+        };
+        for cap in TYPE_DECL_REGEX.captures_iter(&content) {
+            let type_name = &cap[1];
+            if let Some((_, package)) = WELL_KNOWN_CLASSES.iter().find(|(n, _)| *n == type_name) {
+                shadowed.push(GraphWarning {
+                    kind: WarningKind::ShadowedStdlib,
+                    file: name.clone(),
+                    detail: format!("{}.{}", package, type_name),
+                    suggestion: Some("consider renaming it".to_string()),
+                });
+            }
+        }
+    }
+    shadowed
+}
+
+/// Returns the package declared by a source file, if any
+pub fn read_package(path: &Path) -> Option<String> {
+    let content = read_source(path)?;
+    PACKAGE_REGEX
+        .captures(&content)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
 #[derive(Debug, Clone)]
 pub struct Node {
     pub name: String,
     pub path: PathBuf,
     pub deps: Vec<String>,
+    /// The part of `deps` that only comes from `import` statements. Classes
+    /// may import each other, and javac compiles them together, so a cycle
+    /// through one of these edges doesn't stop the build.
+    pub imports: Vec<String>,
     #[allow(dead_code)]
     pub implicit_deps: Vec<String>,
 }
 
-/// Finds all public types (classes, interfaces, enums, records) in the same directory as the given file
-fn find_public_classes_in_dir(file_path: &Path) -> Vec<String> {
+/// The dependency graph of an entrypoint, with the problems found while building it
+pub struct GraphResult {
+    pub graph: HashMap<String, Node>,
+    pub warnings: Vec<GraphWarning>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// A `using` dependency whose file doesn't exist
+    MissingDependency,
+    /// A same-directory type referenced without a `using` directive
+    ImplicitDependency,
+    /// A project type named like a well-known JDK class
+    ShadowedStdlib,
+}
+
+/// A dependency problem, reported as JSON by `build --format json`
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphWarning {
+    pub kind: WarningKind,
+    /// The file the problem was found in
+    pub file: String,
+    /// The missing file, the implicit type, or the shadowed JDK class
+    pub detail: String,
+    pub suggestion: Option<String>,
+}
+
+impl GraphWarning {
+    /// The colored one-line form shown on the terminal
+    pub fn render(&self) -> String {
+        let suggestion = self
+            .suggestion
+            .as_ref()
+            .map(|s| format!(" ({})", s))
+            .unwrap_or_default();
+        match self.kind {
+            WarningKind::MissingDependency => format!(
+                "       {} dependency `{}` not found (declared in `{}`){}",
+                "Error:".red().bold(),
+                self.detail,
+                self.file,
+                suggestion
+            ),
+            WarningKind::ImplicitDependency => format!(
+                "     {} implicit dependency `{}` in `{}`{}",
+                "Warning:".yellow().bold(),
+                self.detail.cyan(),
+                self.file,
+                suggestion
+            ),
+            WarningKind::ShadowedStdlib => format!(
+                "     {} `{}` in `{}` shadows {}{}",
+                "Warning:".yellow().bold(),
+                self.detail.rsplit('.').next().unwrap_or_default().cyan(),
+                self.file,
+                self.detail,
+                suggestion
+            ),
+        }
+    }
+}
+
+/// Prints graph warnings to stderr, in the order they were found
+pub fn print_graph_warnings(warnings: &[GraphWarning]) {
+    for warning in warnings {
+        eprintln!("{}", warning.render());
+    }
+}
+
+/// Finds all public types (classes, interfaces, enums, records) in the same directory as the given file.
+/// With `include_package_private`, non-public top-level types are included too, since
+/// they're just as usable from files in the same directory.
+fn find_public_classes_in_dir(file_path: &Path, include_package_private: bool) -> Vec<String> {
     let mut classes = Vec::new();
 
     // Get the directory containing the file, or use current directory if no parent
@@ -44,14 +280,13 @@ fn find_public_classes_in_dir(file_path: &Path) -> Vec<String> {
         }
 
         // Read the file and check if it has a public type
-        if let Ok(content) = fs::read_to_string(&path) {
+        if let Some(content) = read_source(&path) {
             // Look for public type declarations (class, interface, enum, record, abstract class)
-            // Matches: public class, public interface, public enum, public record, public abstract class
-            let type_regex = Regex::new(
-                r"(?m)^\s*public\s+(?:abstract\s+)?(?:class|interface|enum|record)\s+(\w+)",
-            )
-            .unwrap();
-
+            let type_regex: &Regex = if include_package_private {
+                &TOP_LEVEL_TYPE_REGEX
+            } else {
+                &PUBLIC_TYPE_REGEX
+            };
             for cap in type_regex.captures_iter(&content) {
                 if let Some(class_name) = cap.get(1) {
                     let name = class_name.as_str().to_string();
@@ -64,37 +299,48 @@ fn find_public_classes_in_dir(file_path: &Path) -> Vec<String> {
     classes
 }
 
-/// Detects class references in the code (excluding those in comments and the header)
-fn find_class_references(path: &Path, declared_deps: &[String]) -> Vec<String> {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return Vec::new(),
+/// Detects class references in the code: which of `candidates` a file
+/// references outside comments and its header. Stops early once all of them
+/// are found or after `max_lines` lines (so huge generated files don't
+/// dominate the scan).
+fn find_class_references(
+    path: &Path,
+    declared_deps: &[String],
+    candidates: &HashSet<String>,
+    max_lines: usize,
+) -> Vec<String> {
+    let Some(content) = read_source(path) else {
+        return Vec::new();
     };
 
     let mut references = HashSet::new();
     let mut in_header = true;
     let mut in_block_comment = false;
-    let mut current_class_name: Option<String> = None;
 
     // Get the list of declared dependencies (without .java extension)
     let declared_classes: HashSet<String> = declared_deps
         .iter()
-        .map(|d| d.trim_end_matches(".java").to_string())
+        .filter_map(|d| Path::new(d).file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
         .collect();
 
-    // Extract the current file's type name to exclude it from references
-    // Matches: class, interface, enum, record (with or without public/abstract modifiers)
-    let class_decl_regex = Regex::new(
-        r"(?m)^\s*(?:public\s+)?(?:abstract\s+)?(?:class|interface|enum|record)\s+(\w+)",
-    )
-    .unwrap();
-    if let Some(cap) = class_decl_regex.captures(&content) {
-        if let Some(class_name) = cap.get(1) {
-            current_class_name = Some(class_name.as_str().to_string());
-        }
-    }
+    // Every type the file declares itself (a file may hold several top-level
+    // types as long as only one is public); those are never external references
+    let own_types: HashSet<String> = TYPE_DECL_REGEX
+        .captures_iter(&content)
+        .map(|cap| cap[1].to_string())
+        .collect();
+
+    // Candidates that can still turn up: not declared, not one of the file's own types
+    let wanted = candidates
+        .iter()
+        .filter(|name| !declared_classes.contains(*name) && !own_types.contains(*name))
+        .count();
 
-    for line in content.lines() {
+    for line in content.lines().take(max_lines) {
+        if references.len() == wanted {
+            break;
+        }
         let trimmed = line.trim();
 
         // Track block comments
@@ -130,22 +376,17 @@ fn find_class_references(path: &Path, declared_deps: &[String]) -> Vec<String> {
         }
 
         // Look for class instantiations and references using regex
-        // Matches patterns like: new ClassName(), ClassName variable, ClassName.method()
-        let class_ref_regex = Regex::new(r"\b([A-Z][a-zA-Z0-9_]*)\b").unwrap();
-
-        for cap in class_ref_regex.captures_iter(line) {
+        for cap in CLASS_REF_REGEX.captures_iter(line) {
             if let Some(class_name) = cap.get(1) {
                 let name = class_name.as_str().to_string();
 
-                // Don't include if it's the current file's class
-                if let Some(ref curr_class) = current_class_name {
-                    if &name == curr_class {
-                        continue;
-                    }
+                // Don't include if the file declares this type itself
+                if own_types.contains(&name) {
+                    continue;
                 }
 
                 // Don't include if it's already declared in dependencies
-                if !declared_classes.contains(&name) {
+                if !declared_classes.contains(&name) && candidates.contains(&name) {
                     references.insert(name);
                 }
             }
@@ -155,128 +396,621 @@ fn find_class_references(path: &Path, declared_deps: &[String]) -> Vec<String> {
     references.into_iter().collect()
 }
 
+/// The 1-based line where `name` is first referenced outside comments
+pub fn first_reference_line(path: &Path, name: &str) -> Option<usize> {
+    let content = read_source(path)?;
+    let mut in_block_comment = false;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("/*") {
+            in_block_comment = true;
+        }
+        if in_block_comment {
+            if trimmed.ends_with("*/") {
+                in_block_comment = false;
+            }
+            continue;
+        }
+        if trimmed.starts_with("//") {
+            continue;
+        }
+        if CLASS_REF_REGEX
+            .captures_iter(line)
+            .any(|cap| &cap[1] == name)
+        {
+            return Some(index + 1);
+        }
+    }
+    None
+}
+
 /// Checks for implicit dependencies (public types referenced but not declared) and returns warnings
-pub fn check_implicit_dependencies(path: &Path, declared_deps: &[String]) -> Vec<String> {
-    let public_classes = find_public_classes_in_dir(path);
-    let referenced_classes = find_class_references(path, declared_deps);
+pub fn check_implicit_dependencies(
+    path: &Path,
+    declared_deps: &[String],
+    config: &Config,
+) -> Vec<String> {
+    // Only references to types declared in the same directory matter
+    let public_classes: HashSet<String> =
+        find_public_classes_in_dir(path, config.include_package_private_types)
+            .into_iter()
+            .collect();
 
-    let mut implicit_deps = Vec::new();
+    find_class_references(
+        path,
+        declared_deps,
+        &public_classes,
+        config.reference_scan_lines,
+    )
+}
 
-    for ref_class in referenced_classes {
-        // Check if this reference matches a public class in the same directory
-        if public_classes.contains(&ref_class) {
-            implicit_deps.push(ref_class);
-        }
+/// Collects every `using "X.java"` directive in a piece of comment text
+fn collect_using_directives(text: &str, deps: &mut Vec<String>) {
+    let mut rest = text;
+    while let Some(start) = rest.find("using \"") {
+        rest = &rest[start + 7..];
+        let Some(end) = rest.find('"') else {
+            break;
+        };
+        deps.push(rest[..end].to_string());
+        rest = &rest[end + 1..];
     }
-
-    implicit_deps
 }
 
-pub fn parse_dependencies(path: &Path) -> (Vec<String>, Vec<String>) {
-    let content = fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("Failed to read file: {}", path.display()));
+/// A file's declared dependencies (`using` directives, then imports), the
+/// subset of those that only come from imports, and its implicit dependencies
+pub fn parse_dependencies(path: &Path, config: &Config) -> (Vec<String>, Vec<String>, Vec<String>) {
+    // An unreadable file contributes no dependencies; javac reports it properly
+    let content = read_source(path).unwrap_or_default();
 
     let mut deps = Vec::new();
+    let mut imported = Vec::new();
     let mut in_comment = false;
 
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with("/*") {
-            in_comment = true;
+    // The header is every comment (and blank line) before the first type
+    // declaration; `package` and `import` statements may come before or between
+    // them. A block comment may open and close on the same line, and several may
+    // follow each other
+    'lines: for line in content.lines() {
+        let mut rest = line.trim();
+
+        loop {
+            if in_comment {
+                match rest.find("*/") {
+                    Some(end) => {
+                        collect_using_directives(&rest[..end], &mut deps);
+                        rest = rest[end + 2..].trim_start();
+                        in_comment = false;
+                    }
+                    None => {
+                        collect_using_directives(rest, &mut deps);
+                        continue 'lines;
+                    }
+                }
+            }
+
+            if let Some(import) = rest.strip_prefix("import ") {
+                if let Some(dep) = resolve_import(import, config)
+                    && !imported.contains(&dep)
+                {
+                    imported.push(dep);
+                }
+                continue 'lines;
+            }
+            if rest.is_empty() || rest.starts_with("//") || rest.starts_with("package ") {
+                continue 'lines;
+            }
+
+            match rest.strip_prefix("/*") {
+                Some(after) => {
+                    rest = after;
+                    in_comment = true;
+                }
+                None => break 'lines, // reached code, the header is over
+            }
         }
+    }
+
+    // `using "com/example/Util.java"` and `import com.example.Util;` name the
+    // same node, which then counts as a `using` dependency
+    let mut seen = HashSet::new();
+    deps.retain(|dep| seen.insert(node_key(dep)));
+    imported.retain(|dep| seen.insert(node_key(dep)));
+    deps.extend(imported.iter().cloned());
+
+    // Check for implicit dependencies
+    let implicit_deps = check_implicit_dependencies(path, &deps, config);
+
+    (deps, imported, implicit_deps)
+}
+
+/// Maps an `import com.example.Foo;` to `com/example/Foo.java` under src_dir,
+/// if that file exists. Nested-class imports (`com.example.Foo.Inner`) resolve
+/// to the outer class's file. Static, wildcard, and JDK imports are skipped.
+fn resolve_import(import: &str, config: &Config) -> Option<String> {
+    let name = import.trim().trim_end_matches(';').trim();
+    if name.starts_with("static ")
+        || name.ends_with(".*")
+        || name.starts_with("java.")
+        || name.starts_with("javax.")
+    {
+        return None;
+    }
+
+    let segments: Vec<&str> = name.split('.').collect();
+    (2..=segments.len()).rev().find_map(|len| {
+        let file = format!("{}.java", segments[..len].join("/"));
+        config.src_dir.join(&file).is_file().then_some(file)
+    })
+}
+
+/// The graph key for a dependency entry, which is a path relative to
+/// src_dir: `using "./util/Helper.java"` and `import util.Helper;` both refer
+/// to the node `util/Helper.java`, while `com/a/Util.java` and
+/// `com/b/Util.java` stay two nodes
+pub fn node_key(dep: &str) -> String {
+    Path::new(dep)
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
 
-        if in_comment || line.starts_with("/*") {
-            if let Some(start) = line.find("using \"") {
-                let rest = &line[start + 7..];
-                if let Some(end) = rest.find('"') {
-                    let dep = &rest[..end];
-                    deps.push(dep.to_string());
+/// The graph key for a source file: its path relative to src_dir, or the
+/// path as given for a file outside src_dir
+pub fn source_key(path: &Path, config: &Config) -> String {
+    let relative = path
+        .strip_prefix(&config.src_dir)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            // `Main.java` in the working directory when src_dir is `.`, or
+            // the same directory spelled differently
+            let src_dir = config.src_dir.canonicalize().ok()?;
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let path = parent.canonicalize().ok()?.join(path.file_name()?);
+            path.strip_prefix(&src_dir).ok().map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| path.to_path_buf());
+    node_key(&relative.to_string_lossy())
+}
+
+/// Lists the `.java` files directly inside a directory, sorted by name
+fn list_java_files(dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("java"))
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Every file under `dir`, including subdirectories (except `skip`).
+/// Symlinked directories are only entered with `follow_symlinks`, and no
+/// directory is entered twice, so a link pointing back up the tree can't loop.
+pub fn walk_files(dir: &Path, follow_symlinks: bool, skip: Option<&Path>) -> Vec<PathBuf> {
+    fn walk(
+        dir: &Path,
+        follow_symlinks: bool,
+        skip: Option<&Path>,
+        seen: &mut HashSet<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) {
+        let Ok(canonical) = dir.canonicalize() else {
+            return;
+        };
+        if skip == Some(canonical.as_path()) || !seen.insert(canonical) {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_link = entry.file_type().is_ok_and(|kind| kind.is_symlink());
+            if path.is_dir() {
+                if follow_symlinks || !is_link {
+                    walk(&path, follow_symlinks, skip, seen, files);
                 }
+            } else {
+                files.push(path);
             }
         }
+    }
+
+    let mut files = Vec::new();
+    walk(dir, follow_symlinks, skip, &mut HashSet::new(), &mut files);
+    files
+}
+
+/// Every .java file under src_dir, including subdirectories, sorted by path
+fn walk_java_files(config: &Config) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = walk_files(&config.src_dir, config.follow_symlinks, None)
+        .into_iter()
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("java"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Files a declared dependency could mean: the path as written under src_dir,
+/// or else every file below src_dir whose path ends with it, so
+/// `using "Util.java"` finds `com/example/Util.java` in a package layout.
+/// Exactly one candidate means the dependency resolves. `files` is the
+/// `walk_java_files` listing, taken once per graph build.
+fn dependency_candidates(dep: &str, files: &[PathBuf], config: &Config) -> Vec<PathBuf> {
+    let direct = config.src_dir.join(dep);
+    if direct.exists() {
+        return vec![direct];
+    }
 
-        if line.ends_with("*/") {
-            break; // stop after first comment block
+    files
+        .iter()
+        .filter(|path| path.ends_with(dep))
+        .cloned()
+        .collect()
+}
+
+/// Indexes the top-level types declared anywhere under src_dir. A type in a
+/// file of its own name wins over a same-named type declared elsewhere.
+fn build_class_index(config: &Config) -> HashMap<String, PathBuf> {
+    let files = walk_java_files(config);
+
+    let mut index = HashMap::new();
+    for file in files {
+        let Some(content) = read_source(&file) else {
+            continue;
+        };
+        let package = PACKAGE_REGEX
+            .captures(&content)
+            .map(|cap| cap[1].to_string());
+        for cap in TOP_LEVEL_TYPE_REGEX.captures_iter(&content) {
+            let name = &cap[1];
+            let own_file = file.file_stem().and_then(|stem| stem.to_str()) == Some(name);
+            let mut keys = vec![name.to_string()];
+            keys.extend(
+                package
+                    .as_ref()
+                    .map(|package| format!("{}.{}", package, name)),
+            );
+            for key in keys {
+                if own_file {
+                    index.insert(key, file.clone());
+                } else {
+                    index.entry(key).or_insert_with(|| file.clone());
+                }
+            }
         }
+    }
+    index
+}
+
+/// Finds the source file declaring a type, by simple (`Helper`) or
+/// package-qualified (`com.example.Helper`) name
+pub fn resolve_source_for_class(name: &str, config: &Config) -> Option<PathBuf> {
+    let mut index = CLASS_INDEX.lock().unwrap_or_else(|e| e.into_inner());
+    index
+        .get_or_insert_with(|| build_class_index(config))
+        .get(name)
+        .cloned()
+}
+
+/// The `using` entry for a type: its declaring file relative to src_dir, or
+/// `<name>.java` when no file declares it
+pub fn dependency_file_for(name: &str, config: &Config) -> String {
+    resolve_source_for_class(name, config)
+        .and_then(|path| {
+            path.strip_prefix(&config.src_dir)
+                .ok()
+                .map(|path| path.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| format!("{}.java", name))
+}
+
+/// The JPMS module descriptor, if the project has one at the root of src_dir
+pub fn module_descriptor(config: &Config) -> Option<PathBuf> {
+    let path = config.src_dir.join("module-info.java");
+    path.exists().then_some(path)
+}
+
+/// Reads the module name from a `module-info.java`
+pub fn read_module_name(path: &Path) -> Option<String> {
+    let content = read_source(path)?;
+    MODULE_REGEX
+        .captures(&content)
+        .map(|cap| cap[1].to_string())
+}
+
+/// Whether a source file declares a `main` method
+pub fn has_main_method(path: &Path) -> bool {
+    read_source(path).is_some_and(|content| MAIN_METHOD_REGEX.is_match(&content))
+}
+
+/// Source files in src_dir that declare a `main` method, sorted by name.
+/// Files unchanged since the last scan are answered from `scans` without reading them.
+pub fn find_main_files(config: &Config, scans: &mut HashMap<String, MainScan>) -> Vec<String> {
+    let files = list_java_files(&config.src_dir);
+    scans.retain(|key, _| files.contains(key));
 
-        if !in_comment && !line.starts_with("//") && !line.is_empty() {
-            break; // stop after top comment block
+    let mut mains = Vec::new();
+    for file in files {
+        let path = Path::new(&file);
+        let Some((mtime, len)) = source_stamp(path) else {
+            continue;
+        };
+
+        let has_main = match scans.get(&file) {
+            Some(scan) if scan.mtime == mtime && scan.len == len => scan.has_main,
+            _ => {
+                let has_main = has_main_method(path);
+                scans.insert(
+                    file.clone(),
+                    MainScan {
+                        mtime,
+                        len,
+                        has_main,
+                    },
+                );
+                has_main
+            }
+        };
+
+        if has_main && let Some(name) = path.file_name() {
+            mains.push(name.to_string_lossy().to_string());
         }
     }
+    mains
+}
 
-    // Check for implicit dependencies
-    let implicit_deps = check_implicit_dependencies(path, &deps);
+/// Files named `file_name` anywhere under src_dir that declare a `main`
+/// method, sorted by path
+pub fn find_main_files_named(config: &Config, file_name: &str) -> Vec<PathBuf> {
+    let mut files = walk_java_files(config);
+    files.retain(|path| {
+        path.file_name().and_then(|name| name.to_str()) == Some(file_name) && has_main_method(path)
+    });
+    files
+}
+
+/// Stamp of the `.java` files in a directory: their names, mtimes, and sizes.
+/// A file's implicit dependencies depend on the types its siblings declare,
+/// so any change among them invalidates its cached parse.
+fn sibling_stamp(dir: &Path) -> String {
+    let stamps: Vec<(String, Option<(u64, u64)>)> = list_java_files(dir)
+        .into_iter()
+        .map(|file| {
+            let stamp = source_stamp(Path::new(&file));
+            (file, stamp)
+        })
+        .collect();
+    compute_fingerprint(&stamps)
+}
+
+/// Like `parse_dependencies`, but reuses the cached result when neither the
+/// source nor its siblings (`siblings`, from `sibling_stamp`) have changed
+fn parse_dependencies_cached(
+    path: &Path,
+    config: &Config,
+    siblings: &str,
+    cache: &mut GraphCache,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let key = path.to_string_lossy().to_string();
+    let stamp = source_stamp(path);
+
+    if let Some((mtime, len)) = stamp
+        && let Some(entry) = cache.entries.get(&key)
+        && entry.mtime == mtime
+        && entry.len == len
+        && entry.siblings == siblings
+    {
+        return (
+            entry.deps.clone(),
+            entry.imports.clone(),
+            entry.implicit_deps.clone(),
+        );
+    }
+
+    let (deps, imports, implicit_deps) = parse_dependencies(path, config);
+
+    if let Some((mtime, len)) = stamp {
+        cache.entries.insert(
+            key,
+            ParsedSource {
+                mtime,
+                len,
+                siblings: siblings.to_string(),
+                deps: deps.clone(),
+                imports: imports.clone(),
+                implicit_deps: implicit_deps.clone(),
+            },
+        );
+    }
 
-    (deps, implicit_deps)
+    (deps, imports, implicit_deps)
+}
+
+/// Bumped whenever `parse_dependencies` changes what it accepts, so cached
+/// parses made under the old rules are thrown away
+const HEADER_FORMAT_VERSION: u32 = 6;
+
+/// Names of the classes provided by the prelude directory (sources or compiled)
+pub fn prelude_classes(config: &Config) -> HashSet<String> {
+    let Some(dir) = &config.prelude_dir else {
+        return HashSet::new();
+    };
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    matches!(
+                        path.extension().and_then(|s| s.to_str()),
+                        Some("java") | Some("class")
+                    )
+                })
+                .filter_map(|path| {
+                    path.file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 pub fn build_dependency_graph(
     main: &Path,
-    base_dir: &Path,
-    auto_include_implicit: bool,
-) -> HashMap<String, Node> {
+    config: &Config,
+    graph_cache: &mut GraphCache,
+) -> GraphResult {
     let mut visited = HashSet::new();
     let mut graph = HashMap::new();
+    let mut warnings = Vec::new();
+
+    // Classes may have been added, renamed, or moved since the last build (a
+    // long-running `--watch`), so the index is rebuilt when next needed
+    *CLASS_INDEX.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+    // Implicit dependencies depend on which files exist and on the detection
+    // settings (and header parsing rules), so a change to any invalidates every
+    // cached entry
+    let java_files = walk_java_files(config);
+    let source_files: Vec<String> = java_files
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    let settings =
+        compute_fingerprint(&(HEADER_FORMAT_VERSION, config.include_package_private_types));
+    if graph_cache.source_files != source_files || graph_cache.settings != settings {
+        graph_cache.entries.clear();
+        graph_cache.source_files = source_files;
+        graph_cache.settings = settings;
+    }
+
+    // Prelude classes are always on the classpath, so referencing them is fine
+    let prelude = prelude_classes(config);
+
+    // What dependencies are looked up in, the same for every file
+    struct Sources<'a> {
+        config: &'a Config,
+        java_files: &'a [PathBuf],
+        prelude: &'a HashSet<String>,
+        /// `sibling_stamp` of each directory, worked out once per build
+        sibling_stamps: RefCell<HashMap<PathBuf, String>>,
+    }
 
     fn dfs(
         path: &Path,
-        base: &Path,
+        sources: &Sources,
         visited: &mut HashSet<String>,
         graph: &mut HashMap<String, Node>,
-        auto_include_implicit: bool,
+        graph_cache: &mut GraphCache,
+        warnings: &mut Vec<GraphWarning>,
     ) {
-        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let &Sources {
+            config,
+            java_files,
+            prelude,
+            ..
+        } = sources;
+        let name = source_key(path, config);
         if visited.contains(&name) {
             return;
         }
         visited.insert(name.clone());
 
-        let (mut deps, implicit_deps) = parse_dependencies(path);
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let siblings = sources
+            .sibling_stamps
+            .borrow_mut()
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| sibling_stamp(dir))
+            .clone();
+        let (mut deps, imports, mut implicit_deps) =
+            parse_dependencies_cached(path, config, &siblings, graph_cache);
+        implicit_deps.retain(|dep| !prelude.contains(dep));
+        let auto_include_implicit = config.auto_include_implicit_deps;
 
         // Warn about implicit dependencies
-        if !implicit_deps.is_empty() {
-            let file_name = path.file_name().unwrap().to_string_lossy();
-            for imp_dep in &implicit_deps {
-                if auto_include_implicit {
-                    eprintln!(
-                        "     {} implicit dependency `{}` in `{}`",
-                        "Warning:".yellow().bold(),
-                        imp_dep.cyan(),
-                        file_name
-                    );
-                } else {
-                    eprintln!(
-                        "     {} implicit dependency `{}` in `{}` (add 'using \"{}.java\"' to header)",
-                        "Warning:".yellow().bold(),
-                        imp_dep.cyan(),
-                        file_name,
-                        imp_dep
-                    );
-                }
-            }
+        for imp_dep in &implicit_deps {
+            warnings.push(GraphWarning {
+                kind: WarningKind::ImplicitDependency,
+                file: name.clone(),
+                detail: imp_dep.clone(),
+                suggestion: (!auto_include_implicit).then(|| {
+                    format!(
+                        "add 'using \"{}\"' to header",
+                        dependency_file_for(imp_dep, config)
+                    )
+                }),
+            });
         }
 
         // If auto_include_implicit is enabled, add implicit deps to explicit deps
         if auto_include_implicit {
             for imp_dep in &implicit_deps {
-                let dep_file = format!("{}.java", imp_dep);
+                // Package-private types can live in a file named after another type
+                let dep_file = dependency_file_for(imp_dep, config);
                 if !deps.contains(&dep_file) {
                     deps.push(dep_file);
                 }
             }
         }
 
-        // Recursively resolve dependencies
+        // Recursively resolve dependencies. Each is recorded by its node key,
+        // so `using "Util.java"` becomes `com/example/Util.java`; unresolved
+        // ones stay as written
+        let mut resolved = Vec::new();
+        let mut resolved_imports = Vec::new();
         for dep in &deps {
-            let dep_path = base.join(dep);
-            if dep_path.exists() {
-                dfs(&dep_path, base, visited, graph, auto_include_implicit);
-            } else {
-                eprintln!(
-                    "       {} dependency `{}` not found",
-                    "Error:".red().bold(),
-                    dep
-                );
+            let key = match dependency_candidates(dep, java_files, config).as_slice() {
+                [dep_path] => {
+                    dfs(dep_path, sources, visited, graph, graph_cache, warnings);
+                    source_key(dep_path, config)
+                }
+                candidates => {
+                    let suggestion = (!candidates.is_empty()).then(|| {
+                        let paths: Vec<String> = candidates
+                            .iter()
+                            .map(|path| {
+                                path.strip_prefix(&config.src_dir)
+                                    .unwrap_or(path)
+                                    .to_string_lossy()
+                                    .to_string()
+                            })
+                            .collect();
+                        format!(
+                            "several files match: {}; write the path relative to src_dir",
+                            paths.join(", ")
+                        )
+                    });
+                    warnings.push(GraphWarning {
+                        kind: WarningKind::MissingDependency,
+                        file: name.clone(),
+                        detail: dep.clone(),
+                        suggestion,
+                    });
+                    dep.clone()
+                }
+            };
+            if !resolved.contains(&key) {
+                if imports.contains(dep) {
+                    resolved_imports.push(key.clone());
+                }
+                resolved.push(key);
             }
         }
 
@@ -285,7 +1019,8 @@ pub fn build_dependency_graph(
             Node {
                 name,
                 path: path.to_path_buf(),
-                deps,
+                deps: resolved,
+                imports: resolved_imports,
                 implicit_deps,
             },
         );
@@ -293,56 +1028,668 @@ pub fn build_dependency_graph(
 
     dfs(
         main,
-        base_dir,
+        &Sources {
+            config,
+            java_files: &java_files,
+            prelude: &prelude,
+            sibling_stamps: RefCell::new(HashMap::new()),
+        },
         &mut visited,
         &mut graph,
-        auto_include_implicit,
+        graph_cache,
+        &mut warnings,
     );
-    graph
+    GraphResult { graph, warnings }
 }
 
+/// Declared dependencies whose files don't exist, as (file, dependency) pairs.
+/// Resolved dependencies are always nodes of the graph, so anything else is missing.
+pub fn find_missing_deps(graph: &HashMap<String, Node>) -> Vec<(String, String)> {
+    let mut missing: Vec<(String, String)> = graph
+        .values()
+        .flat_map(|node| {
+            node.deps
+                .iter()
+                .filter(|dep| !graph.contains_key(*dep))
+                .map(|dep| (node.name.clone(), dep.clone()))
+        })
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// Orders the graph so every file comes after its dependencies. A cycle of
+/// `using` dependencies is an error; a cycle through an import is legal Java,
+/// so its closing edge is skipped and the files keep their visiting order.
 pub fn topo_sort(graph: &HashMap<String, Node>) -> Result<Vec<String>, String> {
-    let mut result = Vec::new();
-    let mut visited = HashSet::new();
-    let mut rec_stack = HashSet::new();
+    if let Some(cycle) = find_cycles(graph).first() {
+        return Err(format!(
+            "Circular dependency detected: {}",
+            cycle.join(" -> ")
+        ));
+    }
+    Ok(dependency_order(graph))
+}
 
+/// Every file after its dependencies, as far as cycles allow: an edge back to
+/// a file that is still being visited is skipped
+pub fn dependency_order(graph: &HashMap<String, Node>) -> Vec<String> {
     fn visit(
         node_name: &str,
         graph: &HashMap<String, Node>,
         visited: &mut HashSet<String>,
-        rec_stack: &mut HashSet<String>,
         result: &mut Vec<String>,
-    ) -> Result<(), String> {
-        if rec_stack.contains(node_name) {
-            return Err(format!(
-                "Circular dependency detected involving: {}",
-                node_name
-            ));
+    ) {
+        if !visited.insert(node_name.to_string()) {
+            return;
         }
+        if let Some(node) = graph.get(node_name) {
+            for dep in &node.deps {
+                visit(&node_key(dep), graph, visited, result);
+            }
+        }
+        result.push(node_name.to_string());
+    }
+
+    let mut result = Vec::new();
+    let mut visited = HashSet::new();
+    // Visit all nodes in name order, so the same graph always sorts the same way
+    let mut names: Vec<&String> = graph.keys().collect();
+    names.sort();
+    for node_name in names {
+        visit(node_name, graph, &mut visited, &mut result);
+    }
+    result
+}
 
-        if visited.contains(node_name) {
-            return Ok(());
+/// The dependencies that must be compiled before the file: everything except
+/// plain imports, which javac resolves together with the importing file
+fn using_deps(node: &Node) -> impl Iterator<Item = String> + '_ {
+    node.deps
+        .iter()
+        .map(|dep| node_key(dep))
+        .filter(|dep| !node.imports.iter().any(|import| node_key(import) == *dep))
+}
+
+/// Finds every dependency cycle using Tarjan's strongly-connected components.
+/// Only `using` dependencies count: files that merely import each other are
+/// legal Java and compile fine together.
+///
+/// Each returned cycle is a path that starts and ends at the same file, e.g.
+/// `["A.java", "B.java", "A.java"]`. Cycles are ordered by their first file.
+pub fn find_cycles(graph: &HashMap<String, Node>) -> Vec<Vec<String>> {
+    struct Tarjan<'a> {
+        graph: &'a HashMap<String, Node>,
+        index: usize,
+        indices: HashMap<String, usize>,
+        lowlinks: HashMap<String, usize>,
+        stack: Vec<String>,
+        on_stack: HashSet<String>,
+        components: Vec<Vec<String>>,
+    }
+
+    impl Tarjan<'_> {
+        fn strong_connect(&mut self, name: &str) {
+            self.indices.insert(name.to_string(), self.index);
+            self.lowlinks.insert(name.to_string(), self.index);
+            self.index += 1;
+            self.stack.push(name.to_string());
+            self.on_stack.insert(name.to_string());
+
+            let graph = self.graph;
+            if let Some(node) = graph.get(name) {
+                for dep in using_deps(node) {
+                    if !graph.contains_key(&dep) {
+                        continue;
+                    }
+                    if !self.indices.contains_key(&dep) {
+                        self.strong_connect(&dep);
+                        let low = self.lowlinks[name].min(self.lowlinks[&dep]);
+                        self.lowlinks.insert(name.to_string(), low);
+                    } else if self.on_stack.contains(&dep) {
+                        let low = self.lowlinks[name].min(self.indices[&dep]);
+                        self.lowlinks.insert(name.to_string(), low);
+                    }
+                }
+            }
+
+            if self.lowlinks[name] == self.indices[name] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    let done = member == name;
+                    component.push(member);
+                    if done {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
         }
+    }
 
-        rec_stack.insert(node_name.to_string());
+    let mut names: Vec<&String> = graph.keys().collect();
+    names.sort();
 
-        if let Some(node) = graph.get(node_name) {
-            for dep in &node.deps {
-                visit(dep, graph, visited, rec_stack, result)?;
+    let mut tarjan = Tarjan {
+        graph,
+        index: 0,
+        indices: HashMap::new(),
+        lowlinks: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for name in names {
+        if !tarjan.indices.contains_key(name) {
+            tarjan.strong_connect(name);
+        }
+    }
+
+    let mut cycles: Vec<Vec<String>> = tarjan
+        .components
+        .into_iter()
+        .filter_map(|mut component| {
+            component.sort();
+            let start = component[0].clone();
+            let is_self_loop = graph
+                .get(&start)
+                .is_some_and(|node| using_deps(node).any(|dep| dep == start));
+            if component.len() == 1 && !is_self_loop {
+                return None;
+            }
+            let members: HashSet<String> = component.into_iter().collect();
+            cycle_path(graph, &start, &members)
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
+
+/// Finds the shortest path from `start` back to itself within a component
+fn cycle_path(
+    graph: &HashMap<String, Node>,
+    start: &str,
+    members: &HashSet<String>,
+) -> Option<Vec<String>> {
+    let mut previous: HashMap<String, String> = HashMap::new();
+    let mut queue = std::collections::VecDeque::from([start.to_string()]);
+
+    while let Some(current) = queue.pop_front() {
+        let Some(node) = graph.get(&current) else {
+            continue;
+        };
+        let mut deps: Vec<String> = using_deps(node)
+            .filter(|dep| members.contains(dep))
+            .collect();
+        deps.sort();
+
+        for dep in deps {
+            if dep == start {
+                // Walk back from the node that closes the loop
+                let mut path = vec![current.clone()];
+                while let Some(prev) = path.last().and_then(|step| previous.get(step)) {
+                    path.push(prev.clone());
+                }
+                path.reverse();
+                path.push(start.to_string());
+                return Some(path);
+            }
+            if !previous.contains_key(&dep) {
+                previous.insert(dep.clone(), current.clone());
+                queue.push_back(dep);
             }
         }
+    }
 
-        rec_stack.remove(node_name);
-        visited.insert(node_name.to_string());
-        result.push(node_name.to_string());
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A graph from `(file, deps)` pairs, written the way `using` directives
+    /// may spell them
+    fn graph_of(files: &[(&str, &[&str])]) -> HashMap<String, Node> {
+        files
+            .iter()
+            .map(|(name, deps)| {
+                let node = Node {
+                    name: name.to_string(),
+                    path: PathBuf::from(name),
+                    deps: deps.iter().map(|dep| dep.to_string()).collect(),
+                    imports: Vec::new(),
+                    implicit_deps: Vec::new(),
+                };
+                (name.to_string(), node)
+            })
+            .collect()
+    }
 
-        Ok(())
+    #[test]
+    fn find_cycles_reports_every_disjoint_cycle() {
+        let graph = graph_of(&[
+            ("Main.java", &["com/a/A.java", "util/C.java"]),
+            ("com/a/A.java", &["./com/a/B.java"]),
+            ("com/a/B.java", &["com/a/A.java"]),
+            ("util/C.java", &["./util/D.java"]),
+            ("util/D.java", &["util/C.java"]),
+        ]);
+
+        assert_eq!(
+            find_cycles(&graph),
+            [
+                vec!["com/a/A.java", "com/a/B.java", "com/a/A.java"],
+                vec!["util/C.java", "util/D.java", "util/C.java"],
+            ]
+        );
     }
 
-    // Visit all nodes
-    for node_name in graph.keys() {
-        visit(node_name, graph, &mut visited, &mut rec_stack, &mut result)?;
+    #[test]
+    fn three_file_cycle_is_reported_as_a_full_path() {
+        let graph = graph_of(&[
+            ("A.java", &["B.java"]),
+            ("B.java", &["C.java"]),
+            ("C.java", &["A.java"]),
+        ]);
+
+        let members: HashSet<String> = graph.keys().cloned().collect();
+        assert_eq!(
+            cycle_path(&graph, "A.java", &members).unwrap(),
+            ["A.java", "B.java", "C.java", "A.java"]
+        );
+        assert_eq!(
+            topo_sort(&graph).unwrap_err(),
+            "Circular dependency detected: A.java -> B.java -> C.java -> A.java"
+        );
     }
 
-    Ok(result)
+    #[test]
+    fn files_that_import_each_other_are_not_a_cycle() {
+        let mut graph = graph_of(&[("A.java", &["B.java"]), ("B.java", &["A.java"])]);
+        for node in graph.values_mut() {
+            node.imports = node.deps.clone();
+        }
+
+        assert!(find_cycles(&graph).is_empty());
+        let mut order = topo_sort(&graph).unwrap();
+        order.sort();
+        assert_eq!(order, ["A.java", "B.java"]);
+    }
+
+    #[test]
+    fn an_import_closing_a_using_chain_is_not_a_cycle() {
+        // A uses B, B imports A
+        let mut graph = graph_of(&[("A.java", &["B.java"]), ("B.java", &["A.java"])]);
+        graph.get_mut("B.java").unwrap().imports = vec!["A.java".to_string()];
+
+        assert!(find_cycles(&graph).is_empty());
+        assert_eq!(topo_sort(&graph).unwrap(), ["B.java", "A.java"]);
+    }
+
+    /// Held by tests that build a graph or read the class index, since the
+    /// index is shared by the whole process
+    static CLASS_INDEX_USERS: Mutex<()> = Mutex::new(());
+
+    /// A temporary src_dir holding `(path, contents)` files
+    fn project(files: &[(&str, &str)]) -> (tempfile::TempDir, Config) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let config = Config {
+            src_dir: dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        (dir, config)
+    }
+
+    /// The declared dependencies of one file in `files`
+    fn declared_deps(files: &[(&str, &str)], file: &str) -> Vec<String> {
+        let (dir, config) = project(files);
+        parse_dependencies(&dir.path().join(file), &config).0
+    }
+
+    #[test]
+    fn same_line_block_comments_are_all_part_of_the_header() {
+        let main = "/* using \"A.java\" */\n\
+                    /* using \"B.java\" */ /* using \"C.java\" */\n\
+                    \n\
+                    /*\n * using \"D.java\"\n */\n\
+                    // a line comment\n\
+                    public class Main {}\n";
+
+        assert_eq!(
+            declared_deps(&[("Main.java", main)], "Main.java"),
+            ["A.java", "B.java", "C.java", "D.java"]
+        );
+    }
+
+    #[test]
+    fn header_ends_at_code_even_after_a_same_line_comment() {
+        let main = "/* using \"A.java\" */ public class Main {\n\
+                    /* using \"B.java\" */\n\
+                    }\n";
+
+        assert_eq!(
+            declared_deps(&[("Main.java", main)], "Main.java"),
+            ["A.java"]
+        );
+    }
+
+    #[test]
+    fn comments_after_the_first_declaration_are_not_read() {
+        let main = "/* using \"A.java\" */\npublic class Main {\n    /* using \"B.java\" */\n}\n";
+
+        assert_eq!(
+            declared_deps(&[("Main.java", main)], "Main.java"),
+            ["A.java"]
+        );
+    }
+
+    #[test]
+    fn package_and_import_lines_may_come_before_the_header() {
+        let main = "package app;\n\
+                    \n\
+                    import java.util.List;\n\
+                    import app.util.Helper;\n\
+                    \n\
+                    /* using \"Other.java\" */\n\
+                    public class Main {}\n";
+        let (dir, config) = project(&[
+            ("app/Main.java", main),
+            (
+                "app/util/Helper.java",
+                "package app.util;\npublic class Helper {}\n",
+            ),
+        ]);
+
+        let (deps, imported, _) = parse_dependencies(&dir.path().join("app/Main.java"), &config);
+        assert_eq!(deps, ["Other.java", "app/util/Helper.java"]);
+        assert_eq!(imported, ["app/util/Helper.java"]);
+    }
+
+    #[test]
+    fn imports_resolve_to_files_in_package_folders() {
+        let (_dir, config) = project(&[("com/example/Util.java", "")]);
+
+        assert_eq!(
+            resolve_import("com.example.Util;", &config).as_deref(),
+            Some("com/example/Util.java")
+        );
+        // A nested class lives in its outer class's file
+        assert_eq!(
+            resolve_import("com.example.Util.Inner;", &config).as_deref(),
+            Some("com/example/Util.java")
+        );
+        assert_eq!(resolve_import("com.example.Missing;", &config), None);
+    }
+
+    #[test]
+    fn static_wildcard_and_jdk_imports_are_skipped() {
+        // Each of these would resolve to a file if it weren't skipped
+        let (_dir, config) = project(&[
+            ("com/example/Util.java", ""),
+            ("java/util/List.java", ""),
+            ("javax/swing/JFrame.java", ""),
+        ]);
+
+        for import in [
+            "static com.example.Util.helper;",
+            "com.example.Util.*;",
+            "java.util.List;",
+            "javax.swing.JFrame;",
+        ] {
+            assert_eq!(resolve_import(import, &config), None, "{}", import);
+        }
+    }
+
+    #[test]
+    fn a_dependency_name_matching_several_package_files_is_ambiguous() {
+        let _index = CLASS_INDEX_USERS.lock().unwrap_or_else(|e| e.into_inner());
+        let (dir, config) = project(&[
+            (
+                "Main.java",
+                "/* using \"Util.java\" */\npublic class Main {}\n",
+            ),
+            ("com/a/Util.java", ""),
+            ("com/b/Util.java", ""),
+            ("com/b/Only.java", ""),
+        ]);
+        let files = walk_java_files(&config);
+        let src = dir.path();
+
+        assert_eq!(
+            dependency_candidates("Main.java", &files, &config),
+            [src.join("Main.java")]
+        );
+        assert_eq!(
+            dependency_candidates("Only.java", &files, &config),
+            [src.join("com/b/Only.java")]
+        );
+        assert_eq!(
+            dependency_candidates("Util.java", &files, &config),
+            [src.join("com/a/Util.java"), src.join("com/b/Util.java")]
+        );
+        assert_eq!(
+            dependency_candidates("com/a/Util.java", &files, &config),
+            [src.join("com/a/Util.java")]
+        );
+        assert!(dependency_candidates("Missing.java", &files, &config).is_empty());
+
+        let GraphResult { warnings, .. } =
+            build_dependency_graph(&src.join("Main.java"), &config, &mut GraphCache::default());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::MissingDependency);
+        assert_eq!(
+            warnings[0].suggestion.as_deref(),
+            Some(
+                "several files match: com/a/Util.java, com/b/Util.java; \
+                 write the path relative to src_dir"
+            )
+        );
+    }
+
+    #[test]
+    fn classes_resolve_by_simple_and_package_qualified_name() {
+        let _index = CLASS_INDEX_USERS.lock().unwrap_or_else(|e| e.into_inner());
+        let (dir, config) = project(&[
+            (
+                "app/Helper.java",
+                "package app;\npublic class Helper {}\nclass Extra {}\n",
+            ),
+            // Declares another `Helper`, but app/Helper.java is named after it
+            ("app/Aaa.java", "package app;\nclass Helper {}\n"),
+        ]);
+        let src = dir.path();
+        *CLASS_INDEX.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+        let helper = Some(src.join("app/Helper.java"));
+        assert_eq!(resolve_source_for_class("Helper", &config), helper);
+        assert_eq!(resolve_source_for_class("app.Helper", &config), helper);
+        assert_eq!(resolve_source_for_class("Extra", &config), helper);
+        assert_eq!(resolve_source_for_class("Missing", &config), None);
+        assert_eq!(dependency_file_for("Extra", &config), "app/Helper.java");
+        assert_eq!(dependency_file_for("Missing", &config), "Missing.java");
+    }
+
+    #[test]
+    fn reference_scan_stops_at_the_line_limit() {
+        // A generated file: Early is used at the top, Late only far below
+        let mut source = String::from("public class Generated {\n    Early early;\n");
+        for i in 0..50_000 {
+            source.push_str(&format!("    int field{};\n", i));
+        }
+        source.push_str("    Late late;\n}\n");
+        let (dir, _config) = project(&[("Generated.java", &source)]);
+        let path = dir.path().join("Generated.java");
+        let candidates: HashSet<String> = ["Early", "Late"].map(String::from).into();
+
+        let mut found = find_class_references(&path, &[], &candidates, 20_000);
+        assert_eq!(found, ["Early"]);
+
+        found = find_class_references(&path, &[], &candidates, usize::MAX);
+        found.sort();
+        assert_eq!(found, ["Early", "Late"]);
+    }
+
+    #[test]
+    fn main_scans_are_reused_while_a_file_is_unchanged() {
+        let app = "public class App {\n    public static void main(String[] args) {}\n}\n";
+        let (dir, config) = project(&[("App.java", app), ("Util.java", "class Util {}\n")]);
+        let path = dir.path().join("App.java");
+        let mut scans = HashMap::new();
+
+        assert_eq!(find_main_files(&config, &mut scans), ["App.java"]);
+        assert_eq!(scans.len(), 2);
+
+        // Same size and mtime: the cached scan answers without reading the file
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, app.replace("main(", "nain(")).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified).unwrap();
+        assert_eq!(find_main_files(&config, &mut scans), ["App.java"]);
+
+        // A new stamp means the file is scanned again
+        file.set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(find_main_files(&config, &mut scans).is_empty());
+    }
+
+    #[test]
+    fn latin1_sources_are_still_analyzed() {
+        let (dir, config) = project(&[
+            ("Helper.java", "public class Helper {}\n"),
+            ("Util.java", "public class Util {}\n"),
+        ]);
+        let path = dir.path().join("Main.java");
+        // "café" in Latin-1: 0xE9 on its own isn't valid UTF-8
+        let mut main = b"/* using \"Helper.java\" */\n// caf".to_vec();
+        main.push(0xE9);
+        main.extend_from_slice(b"\npublic class Main {\n    Helper helper;\n    Util util;\n}\n");
+        fs::write(&path, main).unwrap();
+
+        assert!(read_source(&path).unwrap().contains("// café"));
+        let (deps, _, implicit) = parse_dependencies(&path, &config);
+        assert_eq!(deps, ["Helper.java"]);
+        assert_eq!(implicit, ["Util"]);
+        assert_eq!(read_source(&dir.path().join("Missing.java")), None);
+    }
+
+    #[test]
+    fn types_a_file_declares_itself_are_not_implicit_dependencies() {
+        let main = "\
+public class Main {
+    Shape shape = new Circle();
+    Other other;
+}
+
+interface Shape {}
+
+final class Circle implements Shape {}
+";
+        let (dir, mut config) = project(&[("Main.java", main), ("Other.java", "class Other {}\n")]);
+        config.include_package_private_types = true;
+
+        let (_, _, implicit) = parse_dependencies(&dir.path().join("Main.java"), &config);
+        assert_eq!(implicit, ["Other"]);
+    }
+
+    #[test]
+    fn each_warning_kind_serializes_with_its_fields() {
+        let _index = CLASS_INDEX_USERS.lock().unwrap_or_else(|e| e.into_inner());
+        let main = "\
+/* using \"Gone.java\" */
+public class Main {
+    Helper helper;
+}
+
+class List {}
+";
+        let (dir, config) = project(&[
+            ("Main.java", main),
+            ("Helper.java", "public class Helper {}\n"),
+        ]);
+        let GraphResult {
+            graph,
+            mut warnings,
+        } = build_dependency_graph(
+            &dir.path().join("Main.java"),
+            &config,
+            &mut GraphCache::default(),
+        );
+        warnings.extend(find_shadowed_stdlib(&graph));
+
+        let json: Vec<serde_json::Value> = warnings
+            .iter()
+            .map(|warning| serde_json::to_value(warning).unwrap())
+            .collect();
+        assert_eq!(
+            json,
+            [
+                serde_json::json!({
+                    "kind": "implicit_dependency",
+                    "file": "Main.java",
+                    "detail": "Helper",
+                    "suggestion": "add 'using \"Helper.java\"' to header",
+                }),
+                serde_json::json!({
+                    "kind": "missing_dependency",
+                    "file": "Main.java",
+                    "detail": "Gone.java",
+                    "suggestion": null,
+                }),
+                serde_json::json!({
+                    "kind": "shadowed_stdlib",
+                    "file": "Main.java",
+                    "detail": "java.util.List",
+                    "suggestion": "consider renaming it",
+                }),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_are_only_entered_when_following_symlinks() {
+        let (dir, _config) = project(&[
+            ("src/Main.java", ""),
+            ("src/out/Main.class", ""),
+            ("shared/Helper.java", ""),
+        ]);
+        let src = dir.path().join("src");
+        std::os::unix::fs::symlink(dir.path().join("shared"), src.join("shared")).unwrap();
+        // A link back up the tree would loop forever if followed naively
+        std::os::unix::fs::symlink(&src, src.join("loop")).unwrap();
+        let names = |files: Vec<PathBuf>| -> Vec<String> {
+            let mut names: Vec<String> = files
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(&src)
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            names(walk_files(&src, false, None)),
+            ["Main.java", "out/Main.class"]
+        );
+        assert_eq!(
+            names(walk_files(&src, true, None)),
+            ["Main.java", "out/Main.class", "shared/Helper.java"]
+        );
+        let out = src.join("out").canonicalize().unwrap();
+        assert_eq!(
+            names(walk_files(&src, true, Some(&out))),
+            ["Main.java", "shared/Helper.java"]
+        );
+    }
 }
@@ -0,0 +1,41 @@
+use colored::*;
+use std::{io, process::Command};
+
+use crate::build::BuildContext;
+use crate::process::run_command_inherited;
+use crate::report::OutputFormat;
+
+/// Runs a `[hooks]` command through the shell, failing on a non-zero exit.
+/// Its output goes to the terminal, on stderr when stdout carries a report.
+pub fn run_hook(ctx: &BuildContext, name: &str, command: Option<&str>) -> Result<(), String> {
+    let Some(command) = command.filter(|command| !command.trim().is_empty()) else {
+        return Ok(());
+    };
+
+    eprintln!("        {} {}: {}", "Hook".cyan().bold(), name, command);
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    if ctx.format != OutputFormat::Pretty {
+        cmd.stdout(io::stderr());
+    }
+
+    let status = run_command_inherited(&mut cmd, ctx.trace)
+        .map_err(|e| format!("Failed to run {} hook: {}", name, e))?;
+    if !status.success() {
+        return Err(format!(
+            "{} hook `{}` failed with status code: {}",
+            name,
+            command,
+            status.code().unwrap_or(-1)
+        ));
+    }
+    Ok(())
+}
@@ -5,9 +5,7 @@ pub fn init_config(force: bool) -> Result<(), String> {
     let config_path = PathBuf::from("jfu.toml");
 
     if config_path.exists() && !force {
-        return Err(format!(
-            "jfu.toml already exists. Use --force to overwrite."
-        ));
+        return Err("jfu.toml already exists. Use --force to overwrite.".to_string());
     }
 
     let template = r#"# jfu Configuration File
@@ -29,6 +27,10 @@ entrypoint = "Main.java"
 # JVM options to pass when running your program
 jvm_opts = ["-Xmx256m"]
 
+# Extra javac flags, passed in order before the source files
+# [compiler]
+# javac_opts = ["-Xlint:unchecked", "-g"]
+
 # Future features (not yet implemented):
 #
 # [dependencies]
@@ -36,10 +38,6 @@ jvm_opts = ["-Xmx256m"]
 # libs = [
 #     "lib/commons-lang3-3.12.0.jar",
 # ]
-#
-# [compiler]
-# # Additional javac options
-# javac_opts = ["-Xlint:unchecked", "-g"]
 "#;
 
     fs::write(&config_path, template).map_err(|e| format!("Failed to create jfu.toml: {}", e))?;
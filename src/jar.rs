@@ -0,0 +1,118 @@
+use colored::*;
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::build::{BuildContext, quote_argfile_entry};
+use crate::graph::{read_package, walk_files};
+use crate::process::run_command;
+
+/// Name of the jar written into out_dir
+const JAR_NAME: &str = "app.jar";
+
+/// The fully-qualified main class for `--main`: a class name (`Main`,
+/// `com.example.App`), or a source file / `@name` entrypoint whose package
+/// is read from the source
+fn main_class(ctx: &BuildContext, main: Option<String>) -> Result<String, String> {
+    let file = match main {
+        Some(main) if !main.starts_with('@') && !main.ends_with(".java") => return Ok(main),
+        main => ctx.config.resolve_entrypoint(main)?,
+    };
+
+    let path = if Path::new(&file).exists() {
+        PathBuf::from(&file)
+    } else {
+        ctx.config.src_dir.join(&file)
+    };
+    let simple_name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid Java file: {}", file))?;
+    Ok(match read_package(&path) {
+        Some(package) => format!("{}.{}", package, simple_name),
+        None => simple_name,
+    })
+}
+
+/// Packages the compiled classes (and copied resources) in out_dir into a
+/// runnable `app.jar` there, with `Main-Class` set in its manifest
+pub fn jar(ctx: &BuildContext, main: Option<String>) -> Result<(), String> {
+    let out_dir = &ctx.config.out_dir;
+    let main_class = main_class(ctx, main)?;
+
+    let class_file = out_dir.join(format!("{}.class", main_class.replace('.', "/")));
+    if !class_file.exists() {
+        return Err(format!(
+            "{} hasn't been built yet ({} not found).\n   Run `jfu build` first.",
+            main_class,
+            class_file.display()
+        ));
+    }
+
+    // Listed one by one, so jars from earlier runs aren't packed into the new one
+    let mut entries: Vec<PathBuf> = walk_files(out_dir, false, None)
+        .into_iter()
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) != Some("jar"))
+        .filter_map(|path| path.strip_prefix(out_dir).ok().map(Path::to_path_buf))
+        .collect();
+    entries.sort();
+
+    let temp = env::temp_dir();
+    let id = std::process::id();
+    let manifest = temp.join(format!("jfu-manifest-{}.mf", id));
+    let argfile = temp.join(format!("jfu-jar-{}.args", id));
+    fs::write(
+        &manifest,
+        format!("Manifest-Version: 1.0\nMain-Class: {}\n", main_class),
+    )
+    .map_err(|e| format!("Failed to write jar manifest: {}", e))?;
+    // Every entry is `-C <out_dir> <file>`; an @argfile keeps big projects
+    // under OS command-line limits
+    let out_dir_arg = quote_argfile_entry(out_dir);
+    let contents: String = entries
+        .iter()
+        .map(|entry| format!("-C {} {}\n", out_dir_arg, quote_argfile_entry(entry)))
+        .collect();
+    fs::write(&argfile, contents)
+        .map_err(|e| format!("Failed to write jar argument file: {}", e))?;
+
+    let jar_path = out_dir.join(JAR_NAME);
+    let mut cmd = Command::new("jar");
+    cmd.arg("--create")
+        .arg("--file")
+        .arg(&jar_path)
+        .arg("--manifest")
+        .arg(&manifest);
+    let mut arg = OsString::from("@");
+    arg.push(&argfile);
+    cmd.arg(arg);
+
+    let output = run_command(&mut cmd, ctx.trace);
+    let _ = fs::remove_file(&manifest);
+    let _ = fs::remove_file(&argfile);
+    let output = output.map_err(|e| format!("Failed to run jar: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "jar failed:\n{}",
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ));
+    }
+
+    println!(
+        "    {} {} ({} file(s), Main-Class: {})",
+        "Packaged".green().bold(),
+        jar_path.display(),
+        entries.len(),
+        main_class
+    );
+    println!(
+        "   {} java -jar {}",
+        "Run it with".bright_black(),
+        jar_path.display()
+    );
+    Ok(())
+}
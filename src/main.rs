@@ -1,23 +1,39 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
+use std::path::PathBuf;
 
+mod analyze;
 mod build;
 mod cache;
 mod clean;
 mod config;
+mod config_cmd;
+mod diagnostic;
 mod error_format;
 mod graph;
+mod hooks;
 mod init;
+mod jar;
+mod process;
+mod report;
+mod resources;
+mod retry;
 mod run;
 mod syntax;
 mod tree;
+mod watch;
 
+use analyze::{analyze_graph, show_stats};
 use build::{BuildContext, build_files};
 use clean::clean;
 use config::Config;
+use config_cmd::{show_config, validate_config};
 use init::init_config;
+use jar::jar;
+use report::OutputFormat;
 use run::run_file;
 use tree::show_tree;
+use watch::watch;
 
 // ============================================================================
 // CLI Definition
@@ -41,26 +57,120 @@ struct Cli {
     /// Automatically include implicit dependencies in compilation
     #[arg(long, global = true)]
     auto_implicit: bool,
+
+    /// Also detect package-private (non-public) types as implicit dependencies
+    #[arg(long, global = true)]
+    show_hidden: bool,
+
+    /// Fail the build on implicit dependencies instead of warning about them
+    #[arg(long, global = true)]
+    fail_on_implicit: bool,
+
+    /// Log every external command (javac, java, ...) before running it
+    #[arg(long, global = true)]
+    trace: bool,
+
+    /// Pass source files to javac through an @argfile (automatic for large builds)
+    #[arg(long, global = true)]
+    emit_args_file: bool,
+
+    /// Number of worker threads (defaults to the CPU count; 1 runs everything sequentially)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..))]
+    threads: Option<u32>,
+
+    /// Don't repeat warnings from the last compile of files that are up to date
+    #[arg(long, global = true)]
+    no_cached_warnings: bool,
+
+    /// Format errors for this many columns instead of the terminal width (also JFU_WIDTH)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(40..))]
+    width: Option<u32>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Build the specified Java file and its dependencies
     Build {
-        /// Main Java file to build (uses entrypoint from jfu.toml or Main.java if not specified)
+        /// Main Java file to build, or `@name` for a named entrypoint (uses entrypoint from jfu.toml or Main.java if not specified)
         file: Option<String>,
+        /// Report format: pretty (default), json, sarif, or ndjson (printed to stdout)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty)]
+        format: OutputFormat,
+        /// Also write a JSON summary (counts, per-file statuses, timing) to this file
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+        /// Stay running and rebuild whenever a source file changes
+        #[arg(long)]
+        watch: bool,
+        /// Report cache hits and misses, and the cache file's size and stale entries
+        #[arg(long)]
+        cache_stats: bool,
     },
     /// Build and run the specified Java file
     Run {
-        /// Main Java file to run (uses entrypoint from jfu.toml or Main.java if not specified)
+        /// Main Java file to run, or `@name` for a named entrypoint (uses entrypoint from jfu.toml or Main.java if not specified)
         file: Option<String>,
+        /// Run the program N times and report timing (output after the first run is hidden unless --verbose)
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        repeat: u32,
+        /// Build from the entrypoint as usual, but launch the `main` of this dependency instead
+        #[arg(long, value_name = "FILE")]
+        class_from: Option<String>,
+        /// Echo the java command and resolved classpath, and have the JVM print its settings
+        #[arg(long)]
+        verbose_jvm: bool,
+        /// Stay running and rebuild and rerun whenever a source file changes
+        #[arg(long)]
+        watch: bool,
+        /// Arguments for the program's `main`, after `--` (e.g. `jfu run Main.java -- foo -n`)
+        #[arg(last = true)]
+        args: Vec<String>,
     },
     /// Clean build artifacts
     Clean,
+    /// Package the built classes into a runnable out_dir/app.jar
+    Jar {
+        /// Main class (`Main`, `com.example.App`), source file, or `@name` (uses entrypoint from jfu.toml or Main.java if not specified)
+        #[arg(long)]
+        main: Option<String>,
+    },
     /// Show dependency tree
     Tree {
-        /// Main Java file to analyze (uses entrypoint from jfu.toml or Main.java if not specified)
+        /// Main Java file to analyze, or `@name` for a named entrypoint (uses entrypoint from jfu.toml or Main.java if not specified)
+        file: Option<String>,
+        /// Mark each file as [cached] or [dirty] (would be recompiled by the next build)
+        #[arg(long)]
+        show_cached: bool,
+    },
+    /// Analyze the dependency graph
+    Graph {
+        /// Main Java file to analyze, or `@name` for a named entrypoint (uses entrypoint from jfu.toml or Main.java if not specified)
+        file: Option<String>,
+        /// Only report dependency cycles (exits non-zero if any are found)
+        #[arg(long, conflicts_with = "longest_path")]
+        cycles: bool,
+        /// Only print the longest dependency chain from the entrypoint
+        #[arg(long)]
+        longest_path: bool,
+    },
+    /// Show project statistics (files, lines, dependencies, depth, cycles)
+    Stats {
+        /// Main Java file to analyze, or `@name` for a named entrypoint (uses entrypoint from jfu.toml or Main.java if not specified)
         file: Option<String>,
+        /// Print the statistics as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// List what's available to configure
+    List {
+        /// What to list
+        #[arg(value_enum)]
+        kind: ListKind,
     },
     /// Initialize a new jfu.toml configuration file
     Init {
@@ -70,6 +180,24 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective configuration (jfu.toml plus defaults and CLI flags)
+    Show {
+        /// Print as JSON instead of TOML
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check jfu.toml for missing paths, unknown keys, and invalid values
+    Validate,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ListKind {
+    /// Syntax highlighting themes bundled with jfu, for `color_scheme.theme`
+    Themes,
+}
+
 // ============================================================================
 // Main Entry Point
 // ============================================================================
@@ -80,35 +208,136 @@ fn main() {
     let mut config = Config::load();
 
     // CLI flag overrides config file
+    let mut overrides = Vec::new();
     if cli.auto_implicit {
         config.auto_include_implicit_deps = true;
+        overrides.push(("auto_include_implicit_deps", "--auto-implicit"));
+    }
+    if cli.show_hidden {
+        config.include_package_private_types = true;
+        overrides.push(("include_package_private_types", "--show-hidden"));
+    }
+    if cli.fail_on_implicit {
+        config.fail_on_implicit = true;
+        overrides.push(("fail_on_implicit", "--fail-on-implicit"));
+    }
+    if let Some(threads) = cli.threads {
+        config.threads = Some(threads as usize);
+        overrides.push(("threads", "--threads"));
+    }
+
+    if let Some(width) = cli.width {
+        config.width = Some(width as usize);
+        overrides.push(("width", "--width"));
+    } else if let Ok(width) = std::env::var("JFU_WIDTH") {
+        match width.parse::<usize>() {
+            Ok(width) if width >= 40 => {
+                config.width = Some(width);
+                overrides.push(("width", "JFU_WIDTH"));
+            }
+            _ => eprintln!(
+                "{} Ignoring JFU_WIDTH={} (expected a number of columns, at least 40)",
+                "⚠️".yellow(),
+                width
+            ),
+        }
+    }
+
+    // Size the shared pool once, before anything runs on it
+    if let Some(threads) = config.threads
+        && let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+    {
+        eprintln!(
+            "{} Failed to configure {} threads: {}",
+            "⚠️".yellow(),
+            threads,
+            e
+        );
     }
 
     let ctx = BuildContext {
         config: config.clone(),
         verbose: cli.verbose,
         force: cli.force,
+        trace: cli.trace,
+        emit_args_file: cli.emit_args_file,
+        format: match &cli.command {
+            Commands::Build { format, .. } => *format,
+            _ => OutputFormat::Pretty,
+        },
+        report: match &cli.command {
+            Commands::Build { report, .. } => report.clone(),
+            _ => None,
+        },
+        cached_warnings: !cli.no_cached_warnings,
+        cache_stats: matches!(
+            cli.command,
+            Commands::Build {
+                cache_stats: true,
+                ..
+            }
+        ),
     };
 
     let result = match cli.command {
-        Commands::Build { file } => {
-            let file = file
-                .or_else(|| config.entrypoint.clone())
-                .unwrap_or_else(|| "Main.java".to_string());
-            build_files(&ctx, &file)
-        }
-        Commands::Run { file } => {
-            let file = file
-                .or_else(|| config.entrypoint.clone())
-                .unwrap_or_else(|| "Main.java".to_string());
-            run_file(&ctx, &file)
-        }
+        Commands::Build {
+            file, watch: true, ..
+        } => config
+            .resolve_entrypoint(file)
+            .and_then(|file| watch(&ctx, || build_files(&ctx, &file).map(|_| ()))),
+        Commands::Build { file, .. } => config
+            .resolve_entrypoint(file)
+            .and_then(|file| build_files(&ctx, &file).map(|_| ())),
+        Commands::Run {
+            file,
+            repeat,
+            class_from,
+            verbose_jvm,
+            watch: watching,
+            args,
+        } => config.resolve_entrypoint(file).and_then(|file| {
+            let run = || {
+                run_file(
+                    &ctx,
+                    &file,
+                    class_from.as_deref(),
+                    repeat,
+                    verbose_jvm,
+                    &args,
+                )
+            };
+            if watching { watch(&ctx, run) } else { run() }
+        }),
         Commands::Clean => clean(&config),
-        Commands::Tree { file } => {
-            let file = file
-                .or_else(|| config.entrypoint.clone())
-                .unwrap_or_else(|| "Main.java".to_string());
-            show_tree(&config, &file, cli.verbose)
+        Commands::Jar { main } => jar(&ctx, main),
+        Commands::Tree { file, show_cached } => config
+            .resolve_entrypoint(file)
+            .and_then(|file| show_tree(&config, &file, cli.verbose, show_cached)),
+        Commands::Graph {
+            file,
+            cycles,
+            longest_path,
+        } => config
+            .resolve_entrypoint(file)
+            .and_then(|file| analyze_graph(&config, &file, cycles, longest_path)),
+        Commands::Stats { file, json } => config
+            .resolve_entrypoint(file)
+            .and_then(|file| show_stats(&config, &file, json)),
+        Commands::Config { action } => match action {
+            ConfigAction::Show { json } => show_config(&config, json, cli.verbose, &overrides),
+            ConfigAction::Validate => validate_config(&config),
+        },
+        Commands::List { kind } => {
+            match kind {
+                ListKind::Themes => {
+                    for name in syntax::theme_names() {
+                        println!("{}", name);
+                    }
+                }
+            }
+            Ok(())
         }
         Commands::Init { force } => init_config(force),
     };
@@ -0,0 +1,75 @@
+use colored::*;
+use std::{
+    env, io,
+    process::{Child, Command, ExitStatus, Output},
+};
+
+/// Renders a command's program and arguments as a shell-like string
+pub fn format_command(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{}\"", arg)
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Logs a command and its working directory to stderr
+fn trace_command(cmd: &Command) {
+    let cwd = cmd
+        .get_current_dir()
+        .map(|dir| dir.to_path_buf())
+        .or_else(|| env::current_dir().ok())
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "?".to_string());
+    eprintln!(
+        "     {} {} (in {})",
+        "Tracing".magenta().bold(),
+        format_command(cmd),
+        cwd
+    );
+}
+
+/// Runs an external command to completion, logging it to stderr first when tracing
+pub fn run_command(cmd: &mut Command, trace: bool) -> io::Result<Output> {
+    if trace {
+        trace_command(cmd);
+    }
+    cmd.output()
+}
+
+/// Like `run_command`, but the command writes straight to the terminal
+pub fn run_command_inherited(cmd: &mut Command, trace: bool) -> io::Result<ExitStatus> {
+    if trace {
+        trace_command(cmd);
+    }
+    cmd.status()
+}
+
+/// Like `run_command_inherited`, but returns the running child, so output
+/// it was set up to pipe can be read as it arrives
+pub fn spawn_command(cmd: &mut Command, trace: bool) -> io::Result<Child> {
+    if trace {
+        trace_command(cmd);
+    }
+    cmd.spawn()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_with_spaces_are_quoted() {
+        let mut cmd = Command::new("javac");
+        cmd.args(["-d", "out dir", "", "Main.java"]);
+
+        assert_eq!(format_command(&cmd), "javac -d \"out dir\" \"\" Main.java");
+    }
+}
@@ -0,0 +1,389 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{fs, path::Path, time::Duration};
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::graph::{GraphWarning, Node};
+use crate::retry::retry_io;
+
+/// How `jfu build` reports its result on stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, human-readable output
+    #[default]
+    Pretty,
+    /// A JSON build result with per-file statuses, for editors
+    Json,
+    /// SARIF 2.1.0, for code-scanning integrations (e.g. GitHub)
+    Sarif,
+    /// One JSON object per line, written as soon as it's known, for streaming consumers
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// Recompiled in this build
+    Compiled,
+    /// Up to date, taken from the cache
+    Skipped,
+    /// javac reported errors in it; it stays dirty for the next build
+    Failed,
+    /// Error-free, but in a javac run that failed because of other files, so
+    /// no classes were written; it stays dirty for the next build
+    NotCompiled,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileResult {
+    pub file: String,
+    pub status: FileStatus,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// The outcome of a build, as reported by `--format json`
+#[derive(Debug, Serialize)]
+pub struct BuildResult {
+    pub success: bool,
+    pub files: Vec<FileResult>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Dependency problems found while building the graph
+    pub warnings: Vec<GraphWarning>,
+}
+
+impl BuildResult {
+    /// Builds per-file results, attributing each diagnostic to the file it names
+    pub fn new(
+        files: Vec<(&Node, FileStatus)>,
+        diagnostics: Vec<Diagnostic>,
+        warnings: Vec<GraphWarning>,
+    ) -> Self {
+        let count = |node: &Node, severity: Severity| {
+            diagnostics
+                .iter()
+                .filter(|d| d.severity == severity && d.file == node.path)
+                .count()
+        };
+        let files = files
+            .into_iter()
+            .map(|(node, status)| FileResult {
+                file: node.name.clone(),
+                status,
+                errors: count(node, Severity::Error),
+                warnings: count(node, Severity::Warning),
+            })
+            .collect::<Vec<_>>();
+
+        BuildResult {
+            success: !files.iter().any(|file| file.status == FileStatus::Failed),
+            files,
+            diagnostics,
+            warnings,
+        }
+    }
+}
+
+/// Renders a build result in the requested machine-readable format
+pub fn render_report(format: OutputFormat, result: &BuildResult) -> Option<String> {
+    match format {
+        OutputFormat::Pretty => None,
+        OutputFormat::Json => Some(serde_json::to_string_pretty(result).unwrap_or_default()),
+        OutputFormat::Sarif => Some(sarif_report(&result.diagnostics)),
+        OutputFormat::Ndjson => Some(ndjson_report(result)),
+    }
+}
+
+/// A single NDJSON line: `value`'s fields plus a `type` saying what it is
+pub fn ndjson_line<T: Serialize>(kind: &str, value: &T) -> String {
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        object.insert("type".to_string(), json!(kind));
+    }
+    value.to_string()
+}
+
+/// Diagnostics, then per-file statuses, then a final `result` line. Graph
+/// warnings are left out: the build streams them as soon as the graph is built.
+fn ndjson_report(result: &BuildResult) -> String {
+    let mut lines: Vec<String> = result
+        .diagnostics
+        .iter()
+        .map(|diagnostic| ndjson_line("diagnostic", diagnostic))
+        .collect();
+    lines.extend(result.files.iter().map(|file| ndjson_line("file", file)));
+    lines.push(ndjson_line("result", &json!({ "success": result.success })));
+    lines.join("\n")
+}
+
+/// A minimal SARIF 2.1.0 log with one result per diagnostic
+fn sarif_report(diagnostics: &[Diagnostic]) -> String {
+    let results: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut region = json!({ "startLine": diagnostic.line });
+            if let Some(column) = diagnostic.column {
+                region["startColumn"] = json!(column);
+            }
+
+            // SARIF wants relative URIs with forward slashes
+            let uri = diagnostic.file.to_string_lossy().replace('\\', "/");
+            let uri = uri.strip_prefix("./").unwrap_or(&uri);
+
+            json!({
+                "ruleId": diagnostic.rule_id(),
+                "level": match diagnostic.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Note => "note",
+                },
+                "message": { "text": diagnostic.plain_message() },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri },
+                        "region": region,
+                    }
+                }],
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "jfu",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+/// The `--report` file: totals up front for scripts, then the per-file detail
+#[derive(Serialize)]
+struct ReportFile<'a> {
+    success: bool,
+    errors: usize,
+    warnings: usize,
+    compiled: usize,
+    skipped: usize,
+    failed: usize,
+    not_compiled: usize,
+    duration_ms: u128,
+    files: &'a [FileResult],
+    diagnostics: &'a [Diagnostic],
+}
+
+/// Writes a build summary to `path` through a temporary file and a rename, so
+/// readers never see a half-written report
+pub fn write_report_file(
+    path: &Path,
+    result: &BuildResult,
+    duration: Duration,
+) -> Result<(), String> {
+    let severity = |severity: Severity| {
+        result
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let status = |status: FileStatus| result.files.iter().filter(|f| f.status == status).count();
+    let report = ReportFile {
+        success: result.success,
+        errors: severity(Severity::Error),
+        warnings: severity(Severity::Warning),
+        compiled: status(FileStatus::Compiled),
+        skipped: status(FileStatus::Skipped),
+        failed: status(FileStatus::Failed),
+        not_compiled: status(FileStatus::NotCompiled),
+        duration_ms: duration.as_millis(),
+        files: &result.files,
+        diagnostics: &result.diagnostics,
+    };
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize build report: {}", e))?;
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "report".to_string());
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let temp = path.with_file_name(format!(".{}.tmp-{}", name, std::process::id()));
+    retry_io(|| fs::write(&temp, &json))
+        .and_then(|_| retry_io(|| fs::rename(&temp, path)))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("Failed to write build report {}: {}", path.display(), e)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::parse_javac_output;
+    use crate::graph::WarningKind;
+    use std::path::PathBuf;
+
+    fn node(name: &str) -> Node {
+        Node {
+            name: name.to_string(),
+            path: PathBuf::from(format!("./{}", name)),
+            deps: Vec::new(),
+            imports: Vec::new(),
+            implicit_deps: Vec::new(),
+        }
+    }
+
+    /// A failed build: one error, one lint warning, and a missing dependency
+    fn failed_build() -> BuildResult {
+        let broken = node("Broken.java");
+        let lint = node("util/Lint.java");
+        let cached = node("Cached.java");
+        let diagnostics = parse_javac_output(
+            "\
+./Broken.java:3: error: cannot find symbol
+        undefined();
+        ^
+  symbol:   method undefined()
+  location: class Broken
+./util/Lint.java:2: warning: [cast] redundant cast to int
+        int i = (int) 3;
+                ^
+1 error
+1 warning
+",
+        );
+        BuildResult::new(
+            vec![
+                (&cached, FileStatus::Skipped),
+                (&broken, FileStatus::Failed),
+                (&lint, FileStatus::NotCompiled),
+            ],
+            diagnostics,
+            vec![GraphWarning {
+                kind: WarningKind::MissingDependency,
+                file: "Broken.java".to_string(),
+                detail: "Gone.java".to_string(),
+                suggestion: None,
+            }],
+        )
+    }
+
+    #[test]
+    fn pretty_output_has_no_report() {
+        assert!(render_report(OutputFormat::Pretty, &failed_build()).is_none());
+    }
+
+    #[test]
+    fn json_report_has_file_statuses_diagnostics_and_warnings() {
+        let report = render_report(OutputFormat::Json, &failed_build()).unwrap();
+        let report: Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(report["success"], false);
+        let files: Vec<(&str, &str, u64, u64)> = report["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| {
+                (
+                    file["file"].as_str().unwrap(),
+                    file["status"].as_str().unwrap(),
+                    file["errors"].as_u64().unwrap(),
+                    file["warnings"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            [
+                ("Cached.java", "skipped", 0, 0),
+                ("Broken.java", "failed", 1, 0),
+                ("util/Lint.java", "not_compiled", 0, 1),
+            ]
+        );
+
+        let error = &report["diagnostics"][0];
+        assert_eq!(error["file"], "./Broken.java");
+        assert_eq!(error["line"], 3);
+        assert_eq!(error["column"], 9);
+        assert_eq!(error["severity"], "error");
+        assert_eq!(error["message"], "cannot find symbol");
+        assert_eq!(report["warnings"][0]["kind"], "missing_dependency");
+        assert_eq!(report["warnings"][0]["detail"], "Gone.java");
+    }
+
+    #[test]
+    fn ndjson_report_is_one_typed_object_per_line_ending_with_the_result() {
+        let report = render_report(OutputFormat::Ndjson, &failed_build()).unwrap();
+        let lines: Vec<Value> = report
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let types: Vec<&str> = lines
+            .iter()
+            .map(|line| line["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            ["diagnostic", "diagnostic", "file", "file", "file", "result"]
+        );
+        assert_eq!(lines[1]["severity"], "warning");
+        assert_eq!(lines[3]["status"], "failed");
+        assert_eq!(lines[5]["success"], false);
+    }
+
+    #[test]
+    fn sarif_report_has_one_result_per_diagnostic() {
+        let report = render_report(OutputFormat::Sarif, &failed_build()).unwrap();
+        let sarif: Value = serde_json::from_str(&report).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "jfu");
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "javac/error");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[1]["ruleId"], "javac/cast");
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[1]["message"]["text"], "redundant cast to int");
+
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "util/Lint.java");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 17);
+    }
+
+    #[test]
+    fn report_file_totals_match_the_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports/build.json");
+
+        write_report_file(&path, &failed_build(), Duration::from_millis(42)).unwrap();
+        let report: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(report["success"], false);
+        assert_eq!(report["errors"], 1);
+        assert_eq!(report["warnings"], 1);
+        assert_eq!(report["compiled"], 0);
+        assert_eq!(report["skipped"], 1);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["not_compiled"], 1);
+        assert_eq!(report["duration_ms"], 42);
+        assert_eq!(report["files"].as_array().unwrap().len(), 3);
+    }
+}
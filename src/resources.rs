@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::Config;
+
+/// Lists (source, destination) pairs for every configured resource file.
+/// A file lands directly in out_dir; a directory's contents are copied into
+/// out_dir keeping their layout (`res/img/a.png` becomes `out/img/a.png`).
+fn resource_files(config: &Config) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    fn walk(dir: &Path, dest: &Path, files: &mut Vec<(PathBuf, PathBuf)>) -> Result<(), String> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read resource directory {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let target = dest.join(entry.file_name());
+            if path.is_dir() {
+                walk(&path, &target, files)?;
+            } else {
+                files.push((path, target));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    for resource in &config.resources {
+        if !resource.exists() {
+            return Err(format!("Resource not found: {}", resource.display()));
+        }
+        if resource.is_dir() {
+            walk(resource, &config.out_dir, &mut files)?;
+        } else if let Some(name) = resource.file_name() {
+            files.push((resource.clone(), config.out_dir.join(name)));
+        }
+    }
+    Ok(files)
+}
+
+/// A copy is stale when it's missing, a different size, or older than its source
+fn is_stale(source: &Path, dest: &Path) -> bool {
+    let (Ok(source), Ok(dest)) = (fs::metadata(source), fs::metadata(dest)) else {
+        return true;
+    };
+    if source.len() != dest.len() {
+        return true;
+    }
+    match (source.modified(), dest.modified()) {
+        (Ok(source), Ok(dest)) => source > dest,
+        _ => true,
+    }
+}
+
+/// Copies changed resources into out_dir, returning the (source, copy) pairs updated
+pub fn sync_resources(config: &Config) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let mut copied = Vec::new();
+    for (source, dest) in resource_files(config)? {
+        if !is_stale(&source, &dest) {
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::copy(&source, &dest)
+            .map_err(|e| format!("Failed to copy resource {}: {}", source.display(), e))?;
+        copied.push((source, dest));
+    }
+    Ok(copied)
+}
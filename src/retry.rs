@@ -0,0 +1,40 @@
+use std::{io, thread, time::Duration};
+
+/// Attempts made before a transient error is reported
+const ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubled after each failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(25);
+
+/// Errors that often clear up on their own: antivirus scanners and indexers on
+/// Windows briefly lock files they're reading, and network filesystems can
+/// interrupt a call
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::PermissionDenied | io::ErrorKind::Interrupted
+    )
+}
+
+/// Runs a filesystem mutation, retrying transient failures with backoff.
+/// The last error is returned once the attempts run out.
+pub fn retry_io<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if is_transient(&e) => {
+                if attempt == ATTEMPTS {
+                    return Err(io::Error::new(
+                        e.kind(),
+                        format!("{} (still failing after {} attempts)", e, ATTEMPTS),
+                    ));
+                }
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
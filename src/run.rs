@@ -1,43 +1,327 @@
 use colored::*;
-use std::process::Command;
+use std::{
+    env, fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
 
-use crate::build::{BuildContext, build_files};
+use crate::build::{BuildContext, build_files, join_classpath, project_classpath};
 use crate::error_format::format_runtime_errors;
+use crate::graph::{
+    has_main_method, module_descriptor, read_module_name, read_package, source_key,
+};
+use crate::hooks::run_hook;
+use crate::process::{format_command, run_command, spawn_command};
+
+/// Builds `main_file` and runs it, or runs `class_from` (another file in
+/// the same dependency graph) after building from `main_file`. `args` are
+/// passed to the program's `main`.
+pub fn run_file(
+    ctx: &BuildContext,
+    main_file: &str,
+    class_from: Option<&str>,
+    repeat: u32,
+    verbose_jvm: bool,
+    args: &[String],
+) -> Result<(), String> {
+    if is_jshell_script(main_file) {
+        if class_from.is_some() || repeat > 1 {
+            return Err("--class-from and --repeat don't apply to jshell scripts".to_string());
+        }
+        return run_jshell(ctx, main_file, verbose_jvm, args);
+    }
+
+    // The file whose class is launched: cwd first, then src_dir
+    let launch_file = class_from.unwrap_or(main_file);
+    let main_path = if Path::new(launch_file).exists() {
+        PathBuf::from(launch_file)
+    } else {
+        ctx.config.src_dir.join(launch_file)
+    };
+    if class_from.is_some() {
+        if !main_path.exists() {
+            return Err(format!("File not found: {}", launch_file));
+        }
+        if !has_main_method(&main_path) {
+            return Err(format!("`{}` has no main method to run", launch_file));
+        }
+    }
 
-pub fn run_file(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
     // First, build
-    build_files(ctx, main_file)?;
+    let graph = build_files(ctx, main_file)?;
+
+    // Otherwise a stale class left over from another build could be launched
+    let name = source_key(&main_path, &ctx.config);
+    if !graph.contains_key(&name) {
+        return Err(format!(
+            "`{}` is not a dependency of `{}`, so it wasn't built.\n   Add it with 'using \"{}\"' or run it directly with `jfu run {}`.",
+            launch_file, main_file, name, launch_file
+        ));
+    }
+    let main_file = launch_file;
 
     // Extract class name (Main.java -> Main)
-    let class_name = main_file
-        .strip_suffix(".java")
+    let simple_name = Path::new(main_file)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".java"))
         .ok_or_else(|| format!("Invalid Java file: {}", main_file))?;
 
+    // Packaged classes are launched by their fully-qualified name
+    let package = read_package(&main_path);
+    let class_name = match &package {
+        Some(package) => format!("{}.{}", package, simple_name),
+        None => simple_name.to_string(),
+    };
+
+    // Make sure the class is where `java -cp out_dir` will look for it
+    let mut class_file = ctx.config.out_dir.clone();
+    if let Some(package) = &package {
+        class_file.extend(package.split('.'));
+    }
+    class_file.push(format!("{}.class", simple_name));
+    if !class_file.exists() {
+        let declared = match &package {
+            Some(package) => format!("declares `package {};`", package),
+            None => "declares no package".to_string(),
+        };
+        return Err(format!(
+            "Expected main class file {} was not found after building.\n   {} {}, so java would fail to load `{}`.\n   Check that the class name matches the file name and the package matches its folder.",
+            class_file.display(),
+            main_file,
+            declared,
+            class_name
+        ));
+    }
+
+    run_hook(ctx, "pre_run", ctx.config.hooks.pre_run.as_deref())?;
+
     println!("     {} `java {}`", "Running".green().bold(), class_name);
 
     // Run the Java program with optional JVM opts
     let mut cmd = Command::new("java");
-    cmd.arg("-cp").arg(&ctx.config.out_dir);
+    let module_path = &ctx.config.compiler.module_path;
+    let module = module_descriptor(&ctx.config).and_then(|path| read_module_name(&path));
+    match &module {
+        // Modular projects run from the module path: out_dir is the module itself
+        Some(_) => {
+            let mut entries = vec![ctx.config.out_dir.clone()];
+            entries.extend(module_path.iter().cloned());
+            cmd.arg("--module-path").arg(join_classpath(&entries)?);
+        }
+        None => {
+            cmd.arg("-cp").arg(project_classpath(&ctx.config)?);
+            if !module_path.is_empty() {
+                cmd.arg("--module-path").arg(join_classpath(module_path)?);
+                cmd.arg("--add-modules").arg("ALL-MODULE-PATH");
+            }
+        }
+    }
 
     // Add JVM options if specified
     for opt in &ctx.config.jvm_opts {
         cmd.arg(opt);
     }
+    // The JVM's own view of its heap/stack sizes and ergonomics, printed at startup
+    if verbose_jvm {
+        cmd.arg("-XshowSettings:vm");
+    }
+
+    match &module {
+        Some(module) => cmd.arg("-m").arg(format!("{}/{}", module, class_name)),
+        None => cmd.arg(&class_name),
+    };
+    // Everything after the main class goes to the program, not the JVM
+    cmd.args(args);
+
+    if verbose_jvm {
+        print_jvm_command(&cmd);
+    }
+
+    if repeat <= 1 {
+        return run_once(ctx, &mut cmd, true).map(|_| ());
+    }
+
+    // Benchmark mode: only the first run's output is shown unless verbose
+    let mut timings = Vec::new();
+    for run in 1..=repeat {
+        let elapsed = run_once(ctx, &mut cmd, run == 1 || ctx.verbose)?;
+        println!(
+            "    {} run {}/{} in {:.2?}",
+            "Finished".green().bold(),
+            run,
+            repeat,
+            elapsed
+        );
+        timings.push(elapsed);
+    }
+
+    let min = timings.iter().min().copied().unwrap_or_default();
+    let max = timings.iter().max().copied().unwrap_or_default();
+    let mean = timings.iter().sum::<Duration>() / timings.len() as u32;
+    println!(
+        "   {} {} runs: min {:.2?}, mean {:.2?}, max {:.2?}",
+        "Benchmark".green().bold(),
+        timings.len(),
+        min,
+        mean,
+        max
+    );
+
+    Ok(())
+}
+
+/// Whether a file is a jshell script (`.jsh`) rather than a Java source
+pub fn is_jshell_script(file: &str) -> bool {
+    Path::new(file).extension().and_then(|ext| ext.to_str()) == Some("jsh")
+}
+
+/// Runs a jshell script with the project classpath. Scripts aren't compiled
+/// themselves; the entrypoint is built first when one resolves, so they see
+/// up-to-date classes.
+fn run_jshell(
+    ctx: &BuildContext,
+    script: &str,
+    verbose_jvm: bool,
+    args: &[String],
+) -> Result<(), String> {
+    let path = if Path::new(script).exists() {
+        PathBuf::from(script)
+    } else {
+        ctx.config.src_dir.join(script)
+    };
+    if !path.exists() {
+        return Err(format!("File not found: {}", script));
+    }
+    if !args.is_empty() {
+        return Err(format!(
+            "`{}` is a jshell script, which can't take program arguments",
+            script
+        ));
+    }
+
+    // Same lookup as `jfu build` with no file; without an entrypoint the
+    // script gets whatever the last build left in out_dir
+    if let Ok(entrypoint) = ctx.config.resolve_entrypoint(None)
+        && !is_jshell_script(&entrypoint)
+        && (Path::new(&entrypoint).exists() || ctx.config.src_dir.join(&entrypoint).exists())
+    {
+        build_files(ctx, &entrypoint)?;
+    }
+
+    run_hook(ctx, "pre_run", ctx.config.hooks.pre_run.as_deref())?;
+
+    // jshell refuses a --class-path entry that doesn't exist, and nothing may
+    // have been built yet
+    fs::create_dir_all(&ctx.config.out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", ctx.config.out_dir.display(), e))?;
+
+    println!("     {} `jshell {}`", "Running".green().bold(), script);
+
+    // jshell waits at its prompt after the last script unless told to exit
+    let exit_script = env::temp_dir().join(format!("jfu-jshell-exit-{}.jsh", std::process::id()));
+    fs::write(&exit_script, "/exit\n")
+        .map_err(|e| format!("Failed to write jshell exit script: {}", e))?;
+
+    let mut cmd = Command::new("jshell");
+    cmd.arg("--class-path").arg(project_classpath(&ctx.config)?);
+    // `-R` options go to the JVM that runs the snippets, not jshell's own
+    for opt in &ctx.config.jvm_opts {
+        cmd.arg(format!("-R{}", opt));
+    }
+    cmd.arg(&path).arg(&exit_script);
+
+    if verbose_jvm {
+        print_jvm_command(&cmd);
+    }
+
+    // jshell still exits 0 when snippets fail; it only reports them on
+    // stderr, which is passed through and watched for them
+    cmd.stderr(Stdio::piped());
+    let result = spawn_command(&mut cmd, ctx.trace).and_then(|mut child| {
+        let mut failures = 0;
+        if let Some(stderr) = child.stderr.take() {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if line.starts_with("Error:") || line.starts_with("Exception ") {
+                    failures += 1;
+                }
+                eprintln!("{}", line);
+            }
+        }
+        Ok((child.wait()?, failures))
+    });
+    let _ = fs::remove_file(&exit_script);
+    let (status, failures) = result.map_err(|e| format!("Failed to run jshell: {}", e))?;
+    if !status.success() {
+        return Err(format!(
+            "jshell exited with status code: {}",
+            status.code().unwrap_or(-1)
+        ));
+    }
+    if failures > 0 {
+        return Err(format!(
+            "{} snippet(s) in `{}` failed (see the jshell output above)",
+            failures, script
+        ));
+    }
+    Ok(())
+}
+
+/// Echoes the java command and each entry of its class/module path
+fn print_jvm_command(cmd: &Command) {
+    eprintln!("         {} {}", "JVM".cyan().bold(), format_command(cmd));
 
-    cmd.arg(class_name);
+    let mut args = cmd.get_args();
+    while let Some(arg) = args.next() {
+        if arg == "-cp" || arg == "--class-path" || arg == "--module-path" {
+            let Some(paths) = args.next() else {
+                break;
+            };
+            eprintln!(
+                "   {} ({}):",
+                "Resolved".cyan().bold(),
+                arg.to_string_lossy()
+            );
+            for path in env::split_paths(paths) {
+                let missing = if path.exists() { "" } else { " (missing)" };
+                eprintln!("             {}{}", path.display(), missing.yellow());
+            }
+        }
+    }
+}
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run java: {}", e))?;
+/// Splits the JVM's leading `VM settings:` block (ending at a blank line) off stderr
+fn split_vm_settings(stderr: &str) -> (&str, &str) {
+    if !stderr.starts_with("VM settings:") {
+        return ("", stderr);
+    }
+    match stderr.find("\n\n") {
+        Some(end) => stderr.split_at(end + 2),
+        None => (stderr, ""),
+    }
+}
 
-    // Print stdout
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    print!("{}", stdout);
+/// Runs the program once and returns its wall-clock time
+fn run_once(ctx: &BuildContext, cmd: &mut Command, show_output: bool) -> Result<Duration, String> {
+    let start = Instant::now();
+    let output = run_command(cmd, ctx.trace).map_err(|e| format!("Failed to run java: {}", e))?;
+    let elapsed = start.elapsed();
 
-    // Print stderr if any
     let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        eprintln!("\n{}", format_runtime_errors(&stderr));
+    // `-XshowSettings` output isn't an error; show it as-is, ahead of the program's output
+    let (settings, stderr) = split_vm_settings(&stderr);
+    if show_output {
+        eprint!("{}", settings);
+        // Print stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        print!("{}", stdout);
+    }
+
+    // Print stderr if any; failures are always shown
+    if !stderr.is_empty() && (show_output || !output.status.success()) {
+        eprintln!("\n{}", format_runtime_errors(stderr, &ctx.config));
     }
 
     if !output.status.success() {
@@ -47,5 +331,5 @@ pub fn run_file(ctx: &BuildContext, main_file: &str) -> Result<(), String> {
         ));
     }
 
-    Ok(())
+    Ok(elapsed)
 }
@@ -4,16 +4,26 @@ use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use syntect::util::LinesWithEndings;
 
+/// The theme used when `color_scheme.theme` isn't set
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
 lazy_static! {
+    // Loaded on first use, so only builds that print code snippets pay for it
     static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
     static ref THEME_SET: ThemeSet = ThemeSet::load_defaults();
 }
 
-pub fn highlight_java_code(code: &str) -> String {
+/// Highlights Java code for the terminal. An unknown `theme` falls back to
+/// the default one.
+pub fn highlight_java_code(code: &str, theme: &str) -> String {
     let syntax = SYNTAX_SET
         .find_syntax_by_extension("java")
         .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-    let mut h = HighlightLines::new(syntax, &THEME_SET.themes["base16-ocean.dark"]);
+    let theme = THEME_SET
+        .themes
+        .get(theme)
+        .unwrap_or(&THEME_SET.themes[DEFAULT_THEME]);
+    let mut h = HighlightLines::new(syntax, theme);
 
     let mut highlighted = String::new();
     for line in LinesWithEndings::from(code) {
@@ -22,3 +32,69 @@ pub fn highlight_java_code(code: &str) -> String {
     }
     highlighted
 }
+
+/// Names of the bundled syntax highlighting themes, sorted
+pub fn theme_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = THEME_SET.themes.keys().map(String::as_str).collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntect::parsing::{ParseState, ScopeStack};
+
+    #[test]
+    fn java_code_is_parsed_into_java_scopes() {
+        let syntax = SYNTAX_SET.find_syntax_by_extension("java").unwrap();
+        let mut state = ParseState::new(syntax);
+        let mut stack = ScopeStack::new();
+        let mut scopes = Vec::new();
+        let line = "public class Main { String s = \"hi\"; }\n";
+        for (_, op) in state.parse_line(line, &SYNTAX_SET).unwrap() {
+            stack.apply(&op).unwrap();
+            scopes.extend(stack.as_slice().iter().map(|scope| scope.build_string()));
+        }
+
+        assert!(scopes.iter().any(|scope| scope == "source.java"));
+        assert!(
+            scopes
+                .iter()
+                .any(|scope| scope.starts_with("storage.modifier"))
+        );
+        assert!(
+            scopes
+                .iter()
+                .any(|scope| scope.starts_with("string.quoted.double"))
+        );
+    }
+
+    #[test]
+    fn highlighted_code_keeps_the_text_and_adds_colors() {
+        let highlighted = highlight_java_code("int x = 42;", DEFAULT_THEME);
+        assert!(highlighted.contains("\x1b[38;2;"));
+        assert!(highlighted.contains("42"));
+    }
+
+    #[test]
+    fn theme_names_are_sorted_and_include_the_default() {
+        let names = theme_names();
+        assert!(names.len() > 1);
+        assert!(names.is_sorted());
+        assert!(names.contains(&DEFAULT_THEME));
+    }
+
+    #[test]
+    fn an_unknown_theme_falls_back_to_the_default() {
+        let code = "int x = 42;";
+        assert_eq!(
+            highlight_java_code(code, "no such theme"),
+            highlight_java_code(code, DEFAULT_THEME)
+        );
+        assert_ne!(
+            highlight_java_code(code, "Solarized (light)"),
+            highlight_java_code(code, DEFAULT_THEME)
+        );
+    }
+}
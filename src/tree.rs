@@ -4,15 +4,24 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use crate::build::release_version;
+use crate::cache::{
+    compiler_settings_changed, compute_hash, load_cache, needs_rebuild, propagate_dirty, save_cache,
+};
 use crate::config::Config;
-use crate::graph::{Node, build_dependency_graph};
+use crate::graph::{
+    GraphResult, Node, build_dependency_graph, dependency_file_for, node_key, print_graph_warnings,
+    source_key,
+};
 
 fn print_tree(
+    config: &Config,
     graph: &HashMap<String, Node>,
     root: &str,
     indent: usize,
     visited: &mut HashSet<String>,
     show_implicit: bool,
+    dirty: Option<&HashMap<String, bool>>,
 ) {
     if visited.contains(root) {
         println!(
@@ -27,27 +36,45 @@ fn print_tree(
     visited.insert(root.to_string());
 
     if let Some(node) = graph.get(root) {
+        // Cache state annotation for --show-cached
+        let state = match dirty.and_then(|dirty| dirty.get(root)) {
+            Some(true) => format!(" {}", "[dirty]".yellow()),
+            Some(false) => format!(" {}", "[cached]".green()),
+            None => String::new(),
+        };
+
         if indent == 0 {
-            println!("{} {}", "📦".cyan(), node.name.bold().green());
+            println!("{} {}{}", "📦".cyan(), node.name.bold().green(), state);
         } else {
             println!(
-                "{}{} {}",
+                "{}{} {}{}",
                 "  ".repeat(indent),
                 "└─".blue(),
-                node.name.green()
+                node.name.green(),
+                state
             );
         }
 
         for dep in &node.deps {
-            print_tree(graph, dep, indent + 1, visited, show_implicit);
+            print_tree(
+                config,
+                graph,
+                dep,
+                indent + 1,
+                visited,
+                show_implicit,
+                dirty,
+            );
         }
 
         // Show implicit dependencies if enabled (only if not already in explicit deps)
         if show_implicit && !node.implicit_deps.is_empty() {
+            // Several implicit types can live in the same file
+            let mut shown = HashSet::new();
             for imp_dep in &node.implicit_deps {
-                let dep_file = format!("{}.java", imp_dep);
+                let dep_file = node_key(&dependency_file_for(imp_dep, config));
                 // Skip if this implicit dep was auto-included in explicit deps
-                if node.deps.contains(&dep_file) {
+                if node.deps.contains(&dep_file) || !shown.insert(dep_file.clone()) {
                     continue;
                 }
                 println!(
@@ -59,14 +86,27 @@ fn print_tree(
                 );
                 // Recursively show implicit deps if they're in the graph
                 if graph.contains_key(&dep_file) {
-                    print_tree(graph, &dep_file, indent + 2, visited, show_implicit);
+                    print_tree(
+                        config,
+                        graph,
+                        &dep_file,
+                        indent + 2,
+                        visited,
+                        show_implicit,
+                        dirty,
+                    );
                 }
             }
         }
     }
 }
 
-pub fn show_tree(config: &Config, main_file: &str, _verbose: bool) -> Result<(), String> {
+pub fn show_tree(
+    config: &Config,
+    main_file: &str,
+    _verbose: bool,
+    show_cached: bool,
+) -> Result<(), String> {
     // First try the current directory, then fall back to src_dir
     let main_path = if Path::new(main_file).exists() {
         PathBuf::from(main_file)
@@ -78,20 +118,48 @@ pub fn show_tree(config: &Config, main_file: &str, _verbose: bool) -> Result<(),
         return Err(format!("File not found: {}", main_file));
     }
 
-    let graph = build_dependency_graph(
-        &main_path,
-        &config.src_dir,
-        config.auto_include_implicit_deps,
-    );
+    let mut cache = load_cache(&config.cache_file);
+    let GraphResult { graph, warnings } =
+        build_dependency_graph(&main_path, config, &mut cache.graph);
+    save_cache(&config.cache_file, &cache);
+    print_graph_warnings(&warnings);
+
+    // Same check the build uses, including a full rebuild on changed compiler
+    // settings or a different --release
+    let dirty: Option<HashMap<String, bool>> = if show_cached {
+        let release = release_version(config.compiler.release.as_deref(), false)?;
+        let settings_changed = compiler_settings_changed(&cache, &config.compiler, release);
+        let mut dirty = graph
+            .iter()
+            .map(|(name, node)| {
+                (
+                    name.clone(),
+                    needs_rebuild(
+                        node,
+                        &cache,
+                        &config.out_dir,
+                        settings_changed,
+                        &compute_hash(&node.path),
+                    ),
+                )
+            })
+            .collect();
+        propagate_dirty(&graph, &mut dirty);
+        Some(dirty)
+    } else {
+        None
+    };
 
     println!("{} Dependency Tree:\n", "📊".cyan());
     let mut visited = HashSet::new();
     print_tree(
+        config,
         &graph,
-        main_file,
+        &source_key(&main_path, config),
         0,
         &mut visited,
         true, // Always show implicit dependencies
+        dirty.as_ref(),
     );
 
     println!(
@@ -0,0 +1,140 @@
+use colored::*;
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{Config as DebouncerConfig, DebounceEventResult, new_debouncer_opt};
+use std::{
+    collections::BTreeMap,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use crate::build::{BuildContext, status};
+use crate::cache::source_stamp;
+use crate::graph::walk_files;
+use crate::report::OutputFormat;
+
+/// Saves made in quick succession (editors often write several times) count as one change
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+enum Event {
+    Changed(Vec<PathBuf>),
+    Failed(String),
+    Stop,
+}
+
+/// Only Java sources (and jshell scripts) trigger a rebuild; our own class
+/// files and cache don't
+fn is_source_change(path: &Path, out_dir: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("java" | "jsh")
+    ) && !path.starts_with(out_dir)
+}
+
+/// Modification time and size of every source under `roots`. Compiling
+/// reads the sources, and some platforms report those reads as events, so
+/// only a changed snapshot counts as an edit.
+fn source_snapshot(
+    roots: &[PathBuf],
+    out_dir: &Path,
+    follow_symlinks: bool,
+) -> BTreeMap<PathBuf, (u64, u64)> {
+    roots
+        .iter()
+        .flat_map(|root| walk_files(root, follow_symlinks, Some(out_dir)))
+        .filter(|path| is_source_change(path, out_dir))
+        .filter_map(|path| source_stamp(&path).map(|stamp| (path, stamp)))
+        .collect()
+}
+
+/// Runs `action`, then again after every change to a source file under
+/// src_dir (or prelude_dir), until Ctrl-C. The graph is rebuilt on every
+/// run, so new dependency files are picked up.
+pub fn watch(
+    ctx: &BuildContext,
+    mut action: impl FnMut() -> Result<(), String>,
+) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel();
+
+    let follow_symlinks = ctx.config.follow_symlinks;
+    let config = DebouncerConfig::default()
+        .with_timeout(DEBOUNCE)
+        .with_notify_config(notify::Config::default().with_follow_symlinks(follow_symlinks));
+
+    let events = tx.clone();
+    let mut debouncer =
+        new_debouncer_opt::<_, RecommendedWatcher>(config, move |result: DebounceEventResult| {
+            let event = match result {
+                Ok(events) => Event::Changed(events.into_iter().map(|event| event.path).collect()),
+                Err(e) => Event::Failed(e.to_string()),
+            };
+            let _ = events.send(event);
+        })
+        .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+
+    let mut roots = vec![ctx.config.src_dir.clone()];
+    roots.extend(ctx.config.prelude_dir.clone());
+    for root in &roots {
+        debouncer
+            .watcher()
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+    }
+
+    ctrlc::set_handler(move || {
+        let _ = tx.send(Event::Stop);
+    })
+    .map_err(|e| format!("Failed to install Ctrl-C handler: {}", e))?;
+
+    // Event paths are absolute, so compare against an absolute out_dir
+    let out_dir = ctx
+        .config
+        .out_dir
+        .canonicalize()
+        .unwrap_or_else(|_| ctx.config.out_dir.clone());
+
+    let pretty = ctx.format == OutputFormat::Pretty;
+    loop {
+        // Taken first, so an edit made during the run still triggers the next one
+        let snapshot = source_snapshot(&roots, &out_dir, follow_symlinks);
+        // Clear the screen so each run starts fresh, but only for someone
+        // watching pretty output; reports piped elsewhere must stay intact
+        if pretty && io::stdout().is_terminal() {
+            eprint!("\x1b[2J\x1b[H");
+        }
+        if let Err(e) = action() {
+            eprintln!("\n{} {}", "❌".red(), e.red());
+        }
+        status!(
+            ctx,
+            "\n{} Watching {} for changes (Ctrl-C to stop)",
+            "👀".cyan(),
+            roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        // Wait for a change that matters
+        loop {
+            match rx.recv() {
+                Ok(Event::Changed(paths)) => {
+                    if paths.iter().any(|path| is_source_change(path, &out_dir))
+                        && source_snapshot(&roots, &out_dir, follow_symlinks) != snapshot
+                    {
+                        break;
+                    }
+                }
+                Ok(Event::Failed(e)) => {
+                    eprintln!("{} File watcher error: {}", "⚠️".yellow(), e);
+                }
+                Ok(Event::Stop) | Err(_) => {
+                    status!(ctx, "\n{} Stopped watching", "👋".cyan());
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
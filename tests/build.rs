@@ -0,0 +1,329 @@
+mod common;
+
+use common::{Project, printing_main, stderr, stdout};
+
+#[test]
+fn same_named_sources_in_different_folders_do_not_reuse_each_others_classes() {
+    let project = Project::new();
+    project
+        .file("ex1/Main.java", &printing_main("Main", "one"))
+        .file("ex2/Main.java", &printing_main("Main", "two"));
+
+    // Both compile to out/Main.class; each run must get its own class back
+    for (file, expected) in [
+        ("ex1/Main.java", "one"),
+        ("ex2/Main.java", "two"),
+        ("ex1/Main.java", "one"),
+        ("ex2/Main.java", "two"),
+    ] {
+        let output = project.jfu_ok(&["run", file]);
+        assert_eq!(
+            stdout(&output).lines().last(),
+            Some(expected),
+            "jfu run {file}"
+        );
+    }
+}
+
+#[test]
+fn changing_a_constant_recompiles_the_files_that_inline_it() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "/* using \"Limits.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Limits.MAX);\n    }\n}\n",
+        )
+        .file(
+            "Limits.java",
+            "public class Limits {\n    public static final int MAX = 1;\n}\n",
+        );
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("1"));
+
+    // javac copies MAX into Main.class, so Main must be recompiled too
+    project.file(
+        "Limits.java",
+        "public class Limits {\n    public static final int MAX = 2;\n}\n",
+    );
+    let output = project.jfu_ok(&["build", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    for file in report["files"].as_array().unwrap() {
+        assert_eq!(file["status"], "compiled", "{}", file["file"]);
+    }
+
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("2"));
+}
+
+#[test]
+fn cache_built_for_another_release_is_rebuilt_with_a_warning() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[compiler]\nrelease = \"auto\"\n")
+        .file("Main.java", &printing_main("Main", "hi"));
+    project.jfu_ok(&["build"]);
+    let cache: serde_json::Value = serde_json::from_str(&project.read("jfu-cache.json")).unwrap();
+    let release = cache["release"]
+        .as_u64()
+        .expect("auto resolves to the javac version");
+
+    // As if another machine, with an older JDK, built the cache
+    let mut cache = cache;
+    cache["release"] = (release - 1).into();
+    project.file("jfu-cache.json", &cache.to_string());
+
+    let output = project.jfu_ok(&["build", "--format", "json"]);
+    assert!(stderr(&output).contains(&format!(
+        "Cached classes were compiled for Java {}, but this build targets Java {}",
+        release - 1,
+        release
+    )));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["files"][0]["status"], "compiled");
+}
+
+#[test]
+fn nested_class_files_are_tracked_and_removed_with_their_class() {
+    let project = Project::new();
+    project.file(
+        "Main.java",
+        "public class Main {\n    static class Inner {}\n\n    public static void main(String[] args) {\n        Runnable task = new Runnable() {\n            public void run() {\n                System.out.println(new Inner());\n            }\n        };\n        task.run();\n    }\n}\n",
+    );
+    project.jfu_ok(&["build"]);
+
+    let cache: serde_json::Value = serde_json::from_str(&project.read("jfu-cache.json")).unwrap();
+    let tracked: Vec<String> = cache["files"]["Main.java"]["class_files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file.as_str().unwrap().to_string())
+        .collect();
+    for class in ["Main.class", "Main$1.class", "Main$Inner.class"] {
+        assert!(
+            tracked.iter().any(|file| file.ends_with(class)),
+            "{class} not in {tracked:?}"
+        );
+        assert!(project.path("out").join(class).exists(), "{class}");
+    }
+
+    // A missing nested class file means the source is compiled again
+    std::fs::remove_file(project.path("out/Main$1.class")).unwrap();
+    let output = project.jfu_ok(&["build", "--format", "json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["files"][0]["status"], "compiled");
+    assert!(project.path("out/Main$1.class").exists());
+
+    // Nested classes removed from the source don't linger in out_dir
+    project.file("Main.java", &printing_main("Main", "hi"));
+    project.jfu_ok(&["build"]);
+    assert!(project.path("out/Main.class").exists());
+    assert!(!project.path("out/Main$1.class").exists());
+    assert!(!project.path("out/Main$Inner.class").exists());
+    let cache: serde_json::Value = serde_json::from_str(&project.read("jfu-cache.json")).unwrap();
+    assert_eq!(
+        cache["files"]["Main.java"]["class_files"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn failed_atomic_build_leaves_the_previous_output_intact() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "atomic_output = true\n")
+        .file(
+            "Main.java",
+            "/* using \"Helper.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Helper.greeting());\n    }\n}\n",
+        )
+        .file(
+            "Helper.java",
+            "public class Helper {\n    static String greeting() {\n        return \"old\";\n    }\n}\n",
+        );
+    project.jfu_ok(&["build"]);
+    let main_class = std::fs::read(project.path("out/Main.class")).unwrap();
+    let helper_class = std::fs::read(project.path("out/Helper.class")).unwrap();
+
+    // Helper alone would compile, but Main's error fails the whole batch
+    project
+        .file(
+            "Helper.java",
+            "public class Helper {\n    static String greeting() {\n        return \"new\";\n    }\n}\n",
+        )
+        .file(
+            "Main.java",
+            "/* using \"Helper.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        undefined();\n    }\n}\n",
+        );
+    let output = project.jfu(&["build"]);
+    assert!(!output.status.success());
+
+    assert_eq!(
+        std::fs::read(project.path("out/Main.class")).unwrap(),
+        main_class
+    );
+    assert_eq!(
+        std::fs::read(project.path("out/Helper.class")).unwrap(),
+        helper_class
+    );
+    let leftovers: Vec<_> = std::fs::read_dir(project.root())
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().contains("staging"))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn emit_args_file_builds_sources_with_spaces_in_their_path() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "src_dir = \"my src\"\n")
+        .file(
+            "my src/Main.java",
+            "/* using \"Helper.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Helper.NAME);\n    }\n}\n",
+        )
+        .file(
+            "my src/Helper.java",
+            "public class Helper {\n    static final String NAME = \"helper\";\n}\n",
+        );
+
+    let output = project.jfu_ok(&["build", "--emit-args-file", "--verbose"]);
+    assert!(stdout(&output).contains("Passing 2 file(s) via"));
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("helper"));
+}
+
+#[test]
+fn threads_sizes_the_hashing_pool() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "hi"));
+
+    let output = project.jfu_ok(&["build", "--threads", "1", "--verbose"]);
+    assert!(stdout(&output).contains("Hashing 1 file(s) on 1 thread(s)"));
+
+    project.file("jfu.toml", "threads = 3\n");
+    let output = project.jfu_ok(&["build", "--verbose"]);
+    assert!(stdout(&output).contains("on 3 thread(s)"));
+    // The flag wins over jfu.toml
+    let output = project.jfu_ok(&["build", "--threads", "1", "--verbose"]);
+    assert!(stdout(&output).contains("on 1 thread(s)"));
+
+    assert!(!project.jfu(&["build", "--threads", "0"]).status.success());
+}
+
+#[test]
+fn strict_deps_fails_before_javac_listing_every_missing_dependency() {
+    let project = Project::new();
+    project.file(
+        "Main.java",
+        "/* using \"Missing.java\" */\n/* using \"Gone.java\" */\npublic class Main {\n    public static void main(String[] args) {}\n}\n",
+    );
+
+    // Off by default: a warning, and the build goes on
+    project.jfu_ok(&["build"]);
+
+    project.file("jfu.toml", "strict_deps = true\n");
+    let output = project.jfu(&["--trace", "build", "--force"]);
+    assert!(!output.status.success());
+    let log = stderr(&output);
+    assert!(
+        log.contains("2 declared dependencies not found (strict_deps is on):"),
+        "{log}"
+    );
+    assert!(log.contains("`Missing.java` (declared in Main.java)"));
+    assert!(log.contains("`Gone.java` (declared in Main.java)"));
+    assert!(!log.contains("Tracing javac"));
+}
+
+#[test]
+fn javac_runs_in_english_unless_localized_diagnostics_is_on() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "hi"));
+    let javac_line = |output: &std::process::Output| {
+        stderr(output)
+            .lines()
+            .find(|line| line.trim().starts_with("Tracing javac "))
+            .expect("javac was traced")
+            .to_string()
+    };
+
+    let output = project.jfu_ok(&["--trace", "build"]);
+    assert!(javac_line(&output).contains(" -J-Duser.language=en -J-Duser.country=US "));
+
+    project.file("jfu.toml", "localized_diagnostics = true\n");
+    let output = project.jfu_ok(&["--trace", "build", "--force"]);
+    assert!(!javac_line(&output).contains("-Duser.language"));
+}
+
+#[test]
+fn fail_on_implicit_lists_each_undeclared_reference_with_its_line() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "public class Main {\n    public static void main(String[] args) {\n        // Helper is mentioned here, but only used below\n        System.out.println(Helper.NAME);\n    }\n}\n",
+        )
+        .file(
+            "Helper.java",
+            "public class Helper {\n    static final String NAME = \"helper\";\n}\n",
+        );
+
+    // By default it's only a warning, and javac gets to run
+    let output = project.jfu(&["--trace", "build"]);
+    assert!(stderr(&output).contains("implicit dependency `Helper`"));
+    assert!(stderr(&output).contains("Tracing javac"));
+
+    let output = project.jfu(&["--trace", "build", "--fail-on-implicit", "--force"]);
+    assert!(!output.status.success());
+    let log = stderr(&output);
+    assert!(
+        log.contains("1 undeclared dependency (fail_on_implicit is on):"),
+        "{log}"
+    );
+    assert!(
+        log.contains("`Helper` used in Main.java:4 (add 'using \"Helper.java\"' to its header)")
+    );
+    assert!(!log.contains("Tracing javac"));
+
+    // The config key works the same way
+    project.file("jfu.toml", "fail_on_implicit = true\n");
+    assert!(!project.jfu(&["build", "--force"]).status.success());
+}
+
+#[test]
+fn cache_stats_counts_hits_and_misses_of_a_partial_rebuild() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "/* using \"A.java\" */\n/* using \"B.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(A.NAME + B.NAME);\n    }\n}\n",
+        )
+        .file("A.java", "public class A {\n    static String NAME = \"a\";\n}\n")
+        .file("B.java", "public class B {\n    static String NAME = \"b\";\n}\n");
+
+    let output = project.jfu_ok(&["build", "--cache-stats"]);
+    assert!(
+        stdout(&output).contains(
+            "Cache 0 hit(s), 3 miss(es) (3 changed, 0 invalidated by a dependency), 0% hit rate"
+        ),
+        "{}",
+        stdout(&output)
+    );
+
+    // B changed; Main is rebuilt because it depends on B; A is reused
+    project.file(
+        "B.java",
+        "public class B {\n    static String NAME = \"bb\";\n}\n",
+    );
+    let output = project.jfu_ok(&["build", "--cache-stats"]);
+    let out = stdout(&output);
+    assert!(
+        out.contains(
+            "Cache 1 hit(s), 2 miss(es) (1 changed, 1 invalidated by a dependency), 33% hit rate"
+        ),
+        "{out}"
+    );
+    assert!(out.contains("3 entries (0 stale)"), "{out}");
+}
@@ -0,0 +1,81 @@
+//! Helpers for driving the jfu binary against a throwaway project
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// A temporary project directory; jfu reads `jfu.toml` from the working directory
+pub struct Project {
+    dir: TempDir,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Project {
+            dir: tempfile::tempdir().expect("create temp project"),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.dir.path().join(relative)
+    }
+
+    /// Writes a file, creating its parent directories
+    pub fn file(&self, relative: &str, contents: &str) -> &Self {
+        let path = self.path(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent directories");
+        }
+        fs::write(&path, contents).expect("write project file");
+        self
+    }
+
+    pub fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.path(relative)).expect("read project file")
+    }
+
+    /// Runs jfu in the project directory with colors disabled
+    pub fn jfu(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_jfu"))
+            .args(args)
+            .current_dir(self.dir.path())
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("run jfu")
+    }
+
+    /// Runs jfu and panics with its output if it fails
+    pub fn jfu_ok(&self, args: &[&str]) -> Output {
+        let output = self.jfu(args);
+        assert!(
+            output.status.success(),
+            "jfu {} failed\nstdout:\n{}\nstderr:\n{}",
+            args.join(" "),
+            stdout(&output),
+            stderr(&output)
+        );
+        output
+    }
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// A class whose `main` prints `message`
+pub fn printing_main(class: &str, message: &str) -> String {
+    format!(
+        "public class {class} {{\n    public static void main(String[] args) {{\n        System.out.println(\"{message}\");\n    }}\n}}\n"
+    )
+}
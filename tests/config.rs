@@ -0,0 +1,80 @@
+mod common;
+
+use common::{Project, printing_main, stderr, stdout};
+
+#[test]
+fn release_with_version_flags_in_javac_opts_is_rejected() {
+    let project = Project::new();
+    project
+        .file(
+            "jfu.toml",
+            "[compiler]\nrelease = \"17\"\njavac_opts = [\"-source\", \"11\"]\n",
+        )
+        .file("Main.java", &printing_main("Main", "hi"));
+
+    let validate = project.jfu(&["config", "validate"]);
+    assert!(!validate.status.success());
+    assert!(stdout(&validate).contains("release conflicts with -source"));
+
+    let build = project.jfu(&["build"]);
+    assert!(!build.status.success());
+    assert!(!project.path("out/Main.class").exists());
+}
+
+#[test]
+fn unknown_colors_fail_validation() {
+    let project = Project::new();
+    project.file("jfu.toml", "[color_scheme]\nwarning = \"blurple\"\n");
+
+    let output = project.jfu(&["config", "validate"]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("color_scheme.warning `blurple` is not a known color"));
+}
+
+#[test]
+fn known_colors_pass_validation() {
+    let project = Project::new();
+    project.file(
+        "jfu.toml",
+        "[color_scheme]\nerror = \"bright red\"\ninfo = \"magenta\"\n",
+    );
+
+    project.jfu_ok(&["config", "validate"]);
+}
+
+#[test]
+fn config_show_reports_cli_overrides_and_their_source() {
+    let project = Project::new();
+    project.file(
+        "jfu.toml",
+        "auto_include_implicit_deps = false\nmax_width = 100\n",
+    );
+
+    let output = project.jfu_ok(&["--auto-implicit", "--verbose", "config", "show", "--json"]);
+    let config: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(config["auto_include_implicit_deps"], true);
+    assert_eq!(config["max_width"], 100);
+
+    let source = |key: &str| {
+        stderr(&output)
+            .lines()
+            .find_map(|line| {
+                line.trim()
+                    .strip_prefix(key)
+                    .filter(|rest| rest.starts_with(' '))
+                    .map(|rest| rest.trim().to_string())
+            })
+            .unwrap_or_else(|| panic!("no source for {key}"))
+    };
+    assert_eq!(
+        source("auto_include_implicit_deps"),
+        "command line (--auto-implicit)"
+    );
+    assert_eq!(source("max_width"), "jfu.toml");
+    assert_eq!(source("out_dir"), "default");
+
+    // TOML is the default format
+    let output = project.jfu_ok(&["--auto-implicit", "config", "show"]);
+    assert!(stdout(&output).contains("auto_include_implicit_deps = true"));
+    assert!(stdout(&output).contains("max_width = 100"));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{Project, stderr, stdout};
+
+#[test]
+fn implicit_dependencies_follow_changes_to_sibling_files() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "public class Main {\n    public static void main(String[] args) {\n        Helper.help();\n    }\n}\n",
+        )
+        .file("Helper.java", "class Helper {\n    static void help() {}\n}\n");
+
+    // Package-private types aren't implicit-dependency candidates by default
+    let output = project.jfu_ok(&["tree"]);
+    assert!(!stderr(&output).contains("implicit dependency `Helper`"));
+
+    // Same files, but Helper is public now; Main's cached parse is stale
+    project.file(
+        "Helper.java",
+        "public class Helper {\n    static void help() {}\n}\n",
+    );
+    let output = project.jfu_ok(&["tree"]);
+    assert!(stderr(&output).contains("implicit dependency `Helper`"));
+}
+
+#[test]
+fn package_private_types_resolve_to_the_file_declaring_them() {
+    let project = Project::new();
+    project
+        .file(
+            "app/Main.java",
+            "package app;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(new Circle().name());\n    }\n}\n",
+        )
+        .file(
+            "app/Shapes.java",
+            "package app;\n\npublic class Shapes {}\n\nclass Circle {\n    String name() {\n        return \"circle\";\n    }\n}\n",
+        );
+
+    let output = project.jfu_ok(&["--show-hidden", "tree", "app/Main.java"]);
+    assert!(
+        stderr(&output).contains("implicit dependency `Circle` in `app/Main.java` (add 'using \"app/Shapes.java\"' to header)"),
+        "{}",
+        stderr(&output)
+    );
+
+    // Auto-included from Shapes.java rather than a nonexistent Circle.java
+    let output = project.jfu_ok(&["--show-hidden", "--auto-implicit", "run", "app/Main.java"]);
+    assert!(
+        !stderr(&output).contains("not found"),
+        "{}",
+        stderr(&output)
+    );
+    assert_eq!(stdout(&output).lines().last(), Some("circle"));
+}
+
+#[test]
+fn longest_path_prints_the_critical_chain() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "/* using \"Short.java\" */\n/* using \"A.java\" */\npublic class Main {}\n",
+        )
+        .file("Short.java", "public class Short {}\n")
+        .file("A.java", "/* using \"B.java\" */\npublic class A {}\n")
+        .file("B.java", "public class B {}\n");
+
+    let output = project.jfu_ok(&["graph", "--longest-path"]);
+    let out = stdout(&output);
+    assert!(out.contains("Longest dependency chain: 3 file(s)"), "{out}");
+    assert!(out.contains("Main.java -> A.java -> B.java"), "{out}");
+    assert!(out.contains("A change to B.java means recompiling everything along this chain"));
+}
+
+#[cfg(unix)]
+#[test]
+fn dependencies_in_symlinked_folders_resolve_only_with_follow_symlinks() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "src_dir = \"src\"\n")
+        .file(
+            "src/Main.java",
+            "/* using \"Helper.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Helper.NAME);\n    }\n}\n",
+        )
+        .file(
+            "shared/Helper.java",
+            "public class Helper {\n    static final String NAME = \"shared\";\n}\n",
+        );
+    std::os::unix::fs::symlink(project.path("shared"), project.path("src/shared")).unwrap();
+
+    let output = project.jfu(&["tree"]);
+    assert!(
+        stderr(&output).contains("dependency `Helper.java` not found"),
+        "{}",
+        stderr(&output)
+    );
+
+    project.file("jfu.toml", "src_dir = \"src\"\nfollow_symlinks = true\n");
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("shared"));
+}
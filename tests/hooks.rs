@@ -0,0 +1,65 @@
+mod common;
+
+use common::{Project, printing_main, stderr, stdout};
+
+#[test]
+fn failing_pre_build_hook_aborts_the_build() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[hooks]\npre_build = \"exit 3\"\n")
+        .file("Main.java", &printing_main("Main", "hi"));
+
+    let output = project.jfu(&["build"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("pre_build hook `exit 3` failed with status code: 3"));
+    assert!(!project.path("out/Main.class").exists());
+}
+
+// The hooks are sh commands
+#[cfg(unix)]
+#[test]
+fn hooks_run_in_order_around_build_and_run() {
+    let project = Project::new();
+    project
+        .file(
+            "jfu.toml",
+            "[hooks]\n\
+             pre_build = \"sh gen.sh\"\n\
+             post_build = \"echo post_build >> hooks.log\"\n\
+             pre_run = \"echo pre_run >> hooks.log\"\n",
+        )
+        .file(
+            "gen.sh",
+            "echo 'public class Gen { static final String MSG = \"generated\"; }' > Gen.java\n\
+             echo pre_build >> hooks.log\n",
+        )
+        .file(
+            "Main.java",
+            "/* using \"Gen.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Gen.MSG);\n    }\n}\n",
+        );
+
+    // pre_build writes a source the build depends on
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("generated"));
+    assert_eq!(
+        project.read("hooks.log"),
+        "pre_build\npost_build\npre_run\n"
+    );
+
+    // A build alone doesn't run pre_run
+    project.file("hooks.log", "");
+    project.jfu_ok(&["build"]);
+    assert_eq!(project.read("hooks.log"), "pre_build\npost_build\n");
+}
+
+#[test]
+fn hook_output_stays_off_stdout_for_reports() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[hooks]\npost_build = \"echo from the hook\"\n")
+        .file("Main.java", &printing_main("Main", "hi"));
+
+    let output = project.jfu_ok(&["build", "--format", "json"]);
+    serde_json::from_str::<serde_json::Value>(&stdout(&output)).unwrap();
+    assert!(stderr(&output).contains("from the hook"));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::{Project, stdout};
+use serde_json::Value;
+
+/// A project whose only file compiles with a `[cast]` lint warning
+fn project_with_warning() -> Project {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[compiler]\nlint = [\"cast\"]\n")
+        .file(
+            "Main.java",
+            "public class Main {\n    public static void main(String[] args) {\n        int i = (int) 3;\n    }\n}\n",
+        );
+    project
+}
+
+fn is_cast_warning(diagnostic: &Value) -> bool {
+    diagnostic["severity"] == "warning" && diagnostic["message"] == "[cast] redundant cast to int"
+}
+
+#[test]
+fn json_report_of_a_no_op_build_keeps_cached_warnings() {
+    let project = project_with_warning();
+    project.jfu_ok(&["build"]);
+
+    let output = project.jfu_ok(&["build", "--format", "json"]);
+    let report: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["files"][0]["status"], "skipped");
+    assert_eq!(report["files"][0]["warnings"], 1);
+    assert!(
+        report["diagnostics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(is_cast_warning)
+    );
+}
+
+#[test]
+fn ndjson_report_of_a_no_op_build_keeps_cached_warnings() {
+    let project = project_with_warning();
+    project.jfu_ok(&["build"]);
+
+    let output = project.jfu_ok(&["build", "--format", "ndjson"]);
+    let lines: Vec<Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(
+        lines
+            .iter()
+            .any(|line| line["type"] == "diagnostic" && is_cast_warning(line))
+    );
+}
+
+#[test]
+fn sarif_report_of_a_no_op_build_keeps_cached_warnings() {
+    let project = project_with_warning();
+    project.jfu_ok(&["build"]);
+
+    let output = project.jfu_ok(&["build", "--format", "sarif"]);
+    let sarif: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["level"], "warning");
+    assert_eq!(results[0]["ruleId"], "javac/cast");
+}
+
+#[test]
+fn ndjson_build_prints_only_parseable_lines() {
+    let project = Project::new();
+    project
+        .file(
+            "Main.java",
+            "/* using \"Gone.java\" */\npublic class Main {\n    public static void main(String[] args) {\n        undefined();\n    }\n}\n",
+        );
+
+    let output = project.jfu(&["build", "--format", "ndjson"]);
+    assert!(!output.status.success());
+    let lines: Vec<Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{e}: {line:?}")))
+        .collect();
+
+    let types: Vec<&str> = lines
+        .iter()
+        .map(|line| line["type"].as_str().unwrap())
+        .collect();
+    // The graph warning streams out before javac runs
+    assert_eq!(types, ["warning", "diagnostic", "file", "result"]);
+    assert_eq!(lines[0]["detail"], "Gone.java");
+    assert_eq!(lines[1]["severity"], "error");
+    assert_eq!(lines[2]["status"], "failed");
+    assert_eq!(lines[3]["success"], false);
+}
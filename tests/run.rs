@@ -0,0 +1,233 @@
+mod common;
+
+use common::{Project, printing_main, stderr, stdout};
+
+#[test]
+fn jshell_script_runs_and_succeeds() {
+    let project = Project::new();
+    project.file("hello.jsh", "System.out.println(\"hello from jshell\");\n");
+
+    let output = project.jfu_ok(&["run", "hello.jsh"]);
+    assert!(stdout(&output).contains("hello from jshell"));
+}
+
+#[test]
+fn failing_jshell_snippet_exits_with_1() {
+    let project = Project::new();
+    project.file("broken.jsh", "undefinedMethod();\n");
+
+    let output = project.jfu(&["run", "broken.jsh"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Error:"));
+}
+
+#[test]
+fn jshell_script_can_import_a_packaged_project_class() {
+    let project = Project::new();
+    project
+        .file(
+            "com/example/Util.java",
+            "package com.example;\n\npublic class Util {\n    public static String greet() {\n        return \"hi from Util\";\n    }\n}\n",
+        )
+        .file(
+            "Main.java",
+            "import com.example.Util;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Util.greet());\n    }\n}\n",
+        )
+        .file(
+            "try.jsh",
+            "import com.example.Util;\nSystem.out.println(Util.greet());\n",
+        );
+
+    // Nothing is compiled yet: running the script builds the entrypoint first
+    let output = project.jfu_ok(&["run", "try.jsh"]);
+    assert!(stdout(&output).contains("hi from Util"));
+    assert!(project.path("out/com/example/Util.class").exists());
+}
+
+#[test]
+fn java_entrypoint_runs_after_building() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "hello from java"));
+
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("hello from java"));
+}
+
+#[test]
+fn named_entrypoint_runs_the_selected_program() {
+    let project = Project::new();
+    project
+        .file(
+            "jfu.toml",
+            "[entrypoints]\nserver = \"Server.java\"\nclient = \"Client.java\"\n",
+        )
+        .file("Server.java", &printing_main("Server", "server up"))
+        .file("Client.java", &printing_main("Client", "client up"));
+
+    let output = project.jfu_ok(&["run", "@client"]);
+    assert_eq!(stdout(&output).lines().last(), Some("client up"));
+
+    let output = project.jfu(&["run", "@worker"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Available: @client (Client.java), @server (Server.java)"));
+}
+
+#[test]
+fn trace_logs_every_command_before_it_runs() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "traced"));
+
+    let output = project.jfu_ok(&["--trace", "run"]);
+    let log = stderr(&output);
+    let traced: Vec<&str> = log
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Tracing "))
+        .collect();
+    assert_eq!(traced.len(), 2, "{log}");
+    assert!(traced[0].starts_with("javac "), "{}", traced[0]);
+    assert!(traced[0].contains("Main.java"), "{}", traced[0]);
+    assert!(traced[1].starts_with("java "), "{}", traced[1]);
+    assert!(traced.iter().all(|line| line.contains(" (in ")));
+
+    // Without --trace, nothing is logged
+    let output = project.jfu_ok(&["run"]);
+    assert!(!stderr(&output).contains("Tracing"));
+}
+
+#[test]
+fn missing_main_class_file_is_reported_without_launching_java() {
+    let project = Project::new();
+    // Compiles fine, but to Program.class rather than Main.class
+    project.file(
+        "Main.java",
+        "class Program {\n    public static void main(String[] args) {\n        System.out.println(\"hi\");\n    }\n}\n",
+    );
+
+    let output = project.jfu(&["--trace", "run"]);
+    assert!(!output.status.success());
+    let log = stderr(&output);
+    assert!(
+        log.contains("Expected main class file ./out/Main.class was not found after building"),
+        "{log}"
+    );
+    assert!(log.contains("Main.java declares no package, so java would fail to load `Main`"));
+    assert!(!log.contains("Tracing java "));
+    assert!(!log.contains("Could not find or load main class"));
+}
+
+#[test]
+fn repeat_runs_the_program_n_times_and_summarizes() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "tick"));
+
+    let output = project.jfu_ok(&["run", "--repeat", "3"]);
+    let out = stdout(&output);
+    for run in 1..=3 {
+        assert!(out.contains(&format!("Finished run {run}/3 in ")), "{out}");
+    }
+    assert!(out.contains("Benchmark 3 runs: min "), "{out}");
+    assert!(out.contains(", mean ") && out.contains(", max "), "{out}");
+    // Only the first run's output is shown
+    assert_eq!(out.matches("tick").count(), 1, "{out}");
+
+    let output = project.jfu_ok(&["run", "--repeat", "2", "--verbose"]);
+    assert_eq!(stdout(&output).matches("tick").count(), 2);
+}
+
+#[test]
+fn entrypoint_is_detected_from_the_only_main_method() {
+    let project = Project::new();
+    project
+        .file("App.java", &printing_main("App", "detected"))
+        .file("Util.java", "public class Util {}\n");
+
+    let output = project.jfu_ok(&["run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("detected"));
+    assert!(project.read("jfu-cache.json").contains("main_scans"));
+
+    project.file("Tool.java", &printing_main("Tool", "tool"));
+    let output = project.jfu(&["run"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Several files have a main method: App.java, Tool.java"));
+}
+
+#[test]
+fn modular_project_runs_with_the_module_flag() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "src_dir = \"src\"\n")
+        .file("src/module-info.java", "module com.example.app {\n}\n")
+        .file(
+            "src/com/example/app/Main.java",
+            "package com.example.app;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(Main.class.getModule().getName());\n    }\n}\n",
+        );
+
+    let output = project.jfu_ok(&["--trace", "run", "com/example/app/Main.java"]);
+    assert_eq!(stdout(&output).lines().last(), Some("com.example.app"));
+    assert!(project.path("out/module-info.class").exists());
+    let java = stderr(&output)
+        .lines()
+        .find(|line| line.trim().starts_with("Tracing java "))
+        .expect("java was traced")
+        .to_string();
+    assert!(java.contains(" --module-path ./out "), "{java}");
+    assert!(
+        java.contains(" -m com.example.app/com.example.app.Main "),
+        "{java}"
+    );
+    assert!(!java.contains(" -cp "), "{java}");
+}
+
+#[test]
+fn module_path_is_added_to_classpath_projects() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[compiler]\nmodule_path = [\"mods\"]\n")
+        .file("mods/.keep", "")
+        .file("Main.java", &printing_main("Main", "classpath"));
+
+    let output = project.jfu_ok(&["--trace", "run"]);
+    assert_eq!(stdout(&output).lines().last(), Some("classpath"));
+    let log = stderr(&output);
+    let traced: Vec<&str> = log
+        .lines()
+        .filter(|line| line.trim().starts_with("Tracing java"))
+        .collect();
+    assert_eq!(traced.len(), 2);
+    for line in traced {
+        assert!(
+            line.contains(" --module-path mods --add-modules ALL-MODULE-PATH "),
+            "{line}"
+        );
+    }
+}
+
+#[test]
+fn verbose_jvm_echoes_the_command_classpath_and_vm_settings() {
+    let project = Project::new();
+    project
+        .file(
+            "jfu.toml",
+            "jvm_opts = [\"-Xmx64m\"]\nprelude_dir = \"lib\"\n",
+        )
+        .file("Main.java", &printing_main("Main", "hi"));
+
+    let output = project.jfu_ok(&["run", "--verbose-jvm"]);
+    let log = stderr(&output);
+    let separator = if cfg!(windows) { ";" } else { ":" };
+    assert!(
+        log.contains(&format!(
+            "JVM java -cp ./out{separator}lib -Xmx64m -XshowSettings:vm Main"
+        )),
+        "{log}"
+    );
+    assert!(log.contains("Resolved (-cp):"));
+    assert!(log.contains("lib (missing)"));
+    assert!(log.contains("Max. Heap Size: 64.00M"), "{log}");
+    assert!(!log.contains("Runtime Error"));
+    assert_eq!(stdout(&output).lines().last(), Some("hi"));
+
+    // Without the flag, nothing extra is passed or printed
+    let output = project.jfu_ok(&["run"]);
+    assert!(!stderr(&output).contains("VM settings:"));
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::{Project, printing_main, stdout};
+use serde_json::Value;
+
+#[test]
+fn show_cached_marks_classes_built_for_another_release_dirty() {
+    let project = Project::new();
+    project
+        .file("jfu.toml", "[compiler]\nrelease = \"17\"\n")
+        .file("Main.java", &printing_main("Main", "hi"));
+    project.jfu_ok(&["build"]);
+
+    let output = project.jfu_ok(&["tree", "--show-cached"]);
+    assert!(stdout(&output).contains("Main.java [cached]"));
+
+    // As if the cache came from a machine whose "auto" release was 11
+    let mut cache: Value = serde_json::from_str(&project.read("jfu-cache.json")).unwrap();
+    cache["release"] = 11.into();
+    project.file("jfu-cache.json", &cache.to_string());
+
+    let output = project.jfu_ok(&["tree", "--show-cached"]);
+    assert!(stdout(&output).contains("Main.java [dirty]"));
+}
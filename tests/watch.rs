@@ -0,0 +1,56 @@
+mod common;
+
+use common::{Project, printing_main};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+fn signal(pid: u32, signal: &str) {
+    let _ = Command::new("kill")
+        .args([signal, &pid.to_string()])
+        .status();
+}
+
+#[test]
+fn watch_keeps_stdout_to_the_report_for_machine_readable_formats() {
+    let project = Project::new();
+    project.file("Main.java", &printing_main("Main", "hi"));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jfu"))
+        .args(["build", "--watch", "--format", "json"])
+        .current_dir(project.root())
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("run jfu");
+
+    // Don't hang the test run if the status line never shows up on stderr
+    let pid = child.id();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(60));
+        signal(pid, "-KILL");
+    });
+
+    // The status line comes after the first build's report
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    let mut watching = false;
+    for line in stderr.lines() {
+        if line.unwrap().contains("Watching") {
+            watching = true;
+            break;
+        }
+    }
+    signal(pid, "-INT");
+    let output = child.wait_with_output().unwrap();
+
+    assert!(watching, "watch mode never reported that it was watching");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        !stdout.contains('\x1b'),
+        "escape codes on stdout: {stdout:?}"
+    );
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(report["success"], true);
+}